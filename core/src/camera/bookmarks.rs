@@ -0,0 +1,121 @@
+#![allow(non_snake_case)]
+
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use crate::camera::{orientationFromEuler, Camera, CameraMode, CameraPose};
+use crate::storage::{self, StateFormat};
+
+/*
+ * Plain camera state for saving, angles in degrees
+ */
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct CameraState {
+	pub pos: Vec3,
+	pub yaw: f32,
+	pub pitch: f32,
+	// Only used in free mode
+	#[serde(default)]
+	pub roll: f32,
+	pub fov: f32,
+	pub mode: CameraMode,
+}
+
+#[allow(dead_code)]
+impl CameraState {
+	pub fn pose(&self) -> CameraPose {
+		CameraPose {
+			pos: self.pos,
+			orientation: orientationFromEuler(self.yaw, self.pitch, self.roll),
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl Camera {
+	pub fn getState(&self) -> CameraState {
+		CameraState {
+			pos: self.pos,
+			yaw: self.yaw,
+			pitch: self.pitch,
+			roll: self.getRoll(),
+			fov: self.fov,
+			mode: self.mode,
+		}
+	}
+
+	pub fn setState(&mut self, state: &CameraState) {
+		self.setMode(state.mode);
+		self.fov = state.fov;
+		self.setPose(&state.pose());
+	}
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Bookmark {
+	pub name: String,
+	pub state: CameraState,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct CameraBookmarks {
+	pub bookmarks: Vec<Bookmark>,
+	// Last bookmark jumped to
+	#[serde(skip)]
+	pub current: Option<usize>,
+}
+
+#[allow(dead_code)]
+impl CameraBookmarks {
+	pub fn add(&mut self, name: &str, state: CameraState) -> usize {
+		self.bookmarks.push(Bookmark {
+			name: name.to_string(),
+			state,
+		});
+		self.current = Some(self.bookmarks.len() - 1);
+		self.bookmarks.len() - 1
+	}
+
+	pub fn remove(&mut self, index: usize) -> Option<Bookmark> {
+		if index >= self.bookmarks.len() {
+			return None;
+		}
+		self.current = None;
+		Some(self.bookmarks.remove(index))
+	}
+
+	pub fn get(&self, index: usize) -> Option<&Bookmark> {
+		self.bookmarks.get(index)
+	}
+
+	// Wraps around, None if there are no bookmarks
+	pub fn jumpNext(&mut self) -> Option<&Bookmark> {
+		self.step(1)
+	}
+
+	pub fn jumpPrevious(&mut self) -> Option<&Bookmark> {
+		self.step(-1)
+	}
+
+	fn step(&mut self, offset: isize) -> Option<&Bookmark> {
+		let count = self.bookmarks.len() as isize;
+		if count == 0 {
+			return None;
+		}
+		let index = match self.current {
+			Some(current) => (current as isize + offset).rem_euclid(count),
+			None if offset < 0 => count - 1,
+			None => 0,
+		} as usize;
+		self.current = Some(index);
+		self.bookmarks.get(index)
+	}
+
+	pub fn save(&self, key: &str, format: StateFormat) -> Result<(), String> {
+		storage::save(key, self, format)
+	}
+
+	// Empty if nothing has been saved yet
+	pub fn load(key: &str, format: StateFormat) -> Result<Self, String> {
+		Ok(storage::load(key, format)?.unwrap_or_default())
+	}
+}
@@ -0,0 +1,57 @@
+#![allow(non_snake_case)]
+
+use glam::{Mat4, Vec2};
+use crate::camera::ClipDepth;
+use crate::geometry::Frustum;
+
+/*
+ * Orthographic camera for 2D/sprite rendering, 1 unit = 1 pixel at zoom 1, y up
+ */
+pub struct Camera2D {
+	pub pos: Vec2,
+	pub zoom: f32,
+	// Radians
+	pub rotation: f32,
+	// Must match the GL clip control state
+	pub clipDepth: ClipDepth,
+}
+
+impl Default for Camera2D {
+	fn default() -> Camera2D {
+		Camera2D {
+			pos: Vec2::ZERO,
+			zoom: 1.0,
+			rotation: 0.0,
+			clipDepth: ClipDepth::default(),
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl Camera2D {
+	pub fn getViewMatrix(&self) -> Mat4 {
+		Mat4::from_rotation_z(-self.rotation) * Mat4::from_translation(-self.pos.extend(0.0))
+	}
+
+	// Centered on `pos`, depth range covers sprite layers -1000..1000
+	pub fn getProjectionMatrix(&self, width: f32, height: f32) -> Mat4 {
+		let halfWidth = width * 0.5 / self.zoom;
+		let halfHeight = height * 0.5 / self.zoom;
+		match self.clipDepth {
+			ClipDepth::NegativeOneToOne => Mat4::orthographic_rh_gl(-halfWidth, halfWidth, -halfHeight, halfHeight, -1000.0, 1000.0),
+			ClipDepth::ZeroToOne => Mat4::orthographic_rh(-halfWidth, halfWidth, -halfHeight, halfHeight, -1000.0, 1000.0),
+		}
+	}
+
+	pub fn getViewProjectionMatrix(&self, width: f32, height: f32) -> Mat4 {
+		self.getProjectionMatrix(width, height) * self.getViewMatrix()
+	}
+
+	pub fn getFrustum(&self, width: f32, height: f32) -> Frustum {
+		let depthRange = match self.clipDepth {
+			ClipDepth::NegativeOneToOne => (-1.0, 1.0),
+			ClipDepth::ZeroToOne => (0.0, 1.0),
+		};
+		Frustum::fromMatrix(&self.getViewProjectionMatrix(width, height), depthRange)
+	}
+}
@@ -0,0 +1,81 @@
+#![allow(non_snake_case)]
+
+use glam::{EulerRot, Quat, Vec3};
+use crate::camera::{Camera, CameraMode};
+
+/*
+ * 6DOF orientation for `CameraMode::Free`, rotations are applied around the camera's local axes
+ * so there is no pitch limit and no gimbal lock. Identity looks down -Z, same as yaw -90 pitch 0.
+ */
+
+// Degrees, roll is around the view direction (positive tilts the camera's up to the right)
+pub fn orientationFromEuler(yaw: f32, pitch: f32, roll: f32) -> Quat {
+	Quat::from_euler(EulerRot::YXZ, -(yaw + 90.0).to_radians(), pitch.to_radians(), -roll.to_radians())
+}
+
+// Returns (yaw, pitch, roll) in degrees, yaw in -180..180
+pub fn eulerFromOrientation(orientation: Quat) -> (f32, f32, f32) {
+	let (y, x, z) = orientation.to_euler(EulerRot::YXZ);
+	let mut yaw = -y.to_degrees() - 90.0;
+	if yaw < -180.0 {
+		yaw += 360.0;
+	}
+	(yaw, x.to_degrees(), -z.to_degrees())
+}
+
+#[allow(dead_code)]
+impl Camera {
+	// Roll is dropped outside free mode
+	pub fn setOrientation(&mut self, orientation: Quat) {
+		if self.mode == CameraMode::Free {
+			self.orientation = orientation.normalize();
+			self.syncFromOrientation();
+			return;
+		}
+		let (yaw, pitch, _) = eulerFromOrientation(orientation);
+		self.yaw = yaw;
+		self.pitch = pitch.clamp(-89.0, 89.0);
+		self.updateVectors();
+	}
+
+	// Derived from yaw/pitch outside free mode
+	pub fn getOrientation(&self) -> Quat {
+		if self.mode == CameraMode::Free {
+			return self.orientation;
+		}
+		orientationFromEuler(self.yaw, self.pitch, 0.0)
+	}
+
+	// Degrees
+	pub fn getRoll(&self) -> f32 {
+		if self.mode != CameraMode::Free {
+			return 0.0;
+		}
+		eulerFromOrientation(self.orientation).2
+	}
+
+	// Degrees, only has an effect in free mode
+	pub fn processRoll(&mut self, degrees: f32) {
+		if self.mode != CameraMode::Free {
+			return;
+		}
+		self.orientation = (self.orientation * Quat::from_rotation_z(-degrees.to_radians())).normalize();
+		self.syncFromOrientation();
+	}
+
+	// Mouse look around the local up and right axes, `xo`/`yo` already scaled to degrees
+	pub(super) fn freeRotate(&mut self, xo: f32, yo: f32) {
+		self.orientation = (self.orientation * Quat::from_rotation_y(-xo.to_radians()) * Quat::from_rotation_x(yo.to_radians())).normalize();
+		self.syncFromOrientation();
+	}
+
+	// Keeps the direction vectors and yaw/pitch readouts in sync
+	pub(super) fn syncFromOrientation(&mut self) {
+		self.front = self.orientation * Vec3::NEG_Z;
+		self.up = self.orientation * Vec3::Y;
+		self.right = self.orientation * Vec3::X;
+		let (yaw, pitch, _) = eulerFromOrientation(self.orientation);
+		self.yaw = yaw;
+		self.pitch = pitch;
+	}
+}
@@ -0,0 +1,236 @@
+#![allow(non_snake_case)]
+/* Based on https://github.com/bwasty/learn-opengl-rs/blob/master/src/camera.rs */
+
+mod bookmarks;
+mod camera_2d;
+mod free;
+mod motion;
+mod orbit;
+mod path;
+mod picking;
+mod projection;
+mod rig;
+
+use glam::{Mat4, Quat, Vec2, Vec3};
+use serde::{Deserialize, Serialize};
+
+pub use self::bookmarks::{Bookmark, CameraBookmarks, CameraState};
+pub use self::camera_2d::Camera2D;
+pub use self::free::{eulerFromOrientation, orientationFromEuler};
+pub use self::motion::{CameraPose, MotionController};
+pub use self::orbit::OrbitController;
+pub use self::path::{CameraPath, PathKey, PathPlayer};
+pub use self::projection::{ClipDepth, Projection};
+pub use self::rig::FollowRig;
+
+#[derive(Copy, Clone, PartialEq)]
+#[allow(dead_code)]
+pub enum Movement {
+	Up,
+	Down,
+	Forward,
+	Backward,
+	Left,
+	Right,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub enum CameraMode {
+	// First-person, WASD + mouse look
+	Fly,
+	// Rotates around `orbit.target`
+	Orbit,
+	// 6DOF with roll, driven by `orientation`
+	Free,
+}
+
+pub struct Camera {
+	pub pos: Vec3,
+	pub front: Vec3,
+	pub up: Vec3,
+	pub right: Vec3,
+	pub worldUp: Vec3,
+	
+	pub yaw: f32,
+	pub pitch: f32,
+	// Authoritative in free mode, yaw/pitch are derived from it
+	pub orientation: Quat,
+	
+	pub speed: f32,
+	pub sensitivity: f32,
+	pub fov: f32,
+	
+	pub mode: CameraMode,
+	pub orbit: OrbitController,
+	pub motion: MotionController,
+	
+	pub projection: Projection,
+	// Must match the GL clip control state
+	pub clipDepth: ClipDepth,
+	// Pixels, only used by pixel perfect orthographic
+	pub viewportSize: Vec2,
+}
+
+impl Default for Camera {
+	fn default() -> Camera {
+		let mut camera = Camera {
+			pos: Vec3::ZERO,
+			front: Vec3::NEG_Z,
+			up: Vec3::ZERO,
+			right: Vec3::ZERO,
+			worldUp: Vec3::Y,
+			
+			yaw: -90.0,
+			pitch: 0.0,
+			orientation: Quat::IDENTITY,
+			
+			speed: 2.5,
+			sensitivity: 0.1,
+			fov: 45.0,
+			
+			mode: CameraMode::Fly,
+			orbit: OrbitController::default(),
+			motion: MotionController::default(),
+			
+			projection: Projection::default(),
+			clipDepth: ClipDepth::default(),
+			viewportSize: Vec2::ONE,
+		};
+		camera.updateVectors();
+		camera
+	}
+}
+
+#[allow(dead_code)]
+impl Camera {
+	pub fn getViewMatrix(&self) -> Mat4 {
+		self.viewMatrix(self.pos, self.front, self.up)
+	}
+	
+	// Between `previous` and the current pose, for rendering in between fixed updates
+	pub fn getInterpolatedViewMatrix(&self, previous: &CameraPose, alpha: f32) -> Mat4 {
+		let pose = previous.lerp(&self.getPose(), alpha);
+		self.viewMatrix(pose.pos, pose.orientation * Vec3::NEG_Z, pose.orientation * Vec3::Y)
+	}
+	
+	fn viewMatrix(&self, pos: Vec3, front: Vec3, up: Vec3) -> Mat4 {
+		if let Projection::Orthographic { zoom, pixelPerfect: true, .. } = self.projection {
+			// Snap to whole pixels on the XY plane so sprites don't shimmer while moving
+			let zoom = zoom.round().max(1.0);
+			let pos = Vec3::new((pos.x * zoom).round() / zoom, (pos.y * zoom).round() / zoom, pos.z);
+			return Mat4::look_at_rh(pos, pos + front, up);
+		}
+		Mat4::look_at_rh(pos, pos + front, up)
+	}
+	
+	pub fn getProjectionMatrix(&self, aspect: f32) -> Mat4 {
+		self.projection.getMatrix(self.fov, aspect, self.viewportSize, self.clipDepth)
+	}
+	
+	pub fn getViewProjectionMatrix(&self, aspect: f32) -> Mat4 {
+		self.getProjectionMatrix(aspect) * self.getViewMatrix()
+	}
+	
+	pub fn getInterpolatedViewProjectionMatrix(&self, aspect: f32, previous: &CameraPose, alpha: f32) -> Mat4 {
+		self.getProjectionMatrix(aspect) * self.getInterpolatedViewMatrix(previous, alpha)
+	}
+	
+	pub fn setViewportSize(&mut self, width: f32, height: f32) {
+		self.viewportSize = Vec2::new(width.max(1.0), height.max(1.0));
+	}
+	
+	pub fn setMode(&mut self, mode: CameraMode) {
+		if mode == CameraMode::Orbit && self.mode != CameraMode::Orbit {
+			// Keep the current view, orbit around the point in front of the camera
+			self.orbit.target = self.pos + self.front * self.orbit.distance;
+		}
+		if mode == CameraMode::Free && self.mode != CameraMode::Free {
+			self.orientation = orientationFromEuler(self.yaw, self.pitch, 0.0);
+		}
+		let leavingFree = self.mode == CameraMode::Free && mode != CameraMode::Free;
+		self.mode = mode;
+		if leavingFree {
+			// Roll is dropped, yaw/pitch are already in sync
+			self.pitch = self.pitch.clamp(-89.0, 89.0);
+			self.updateVectors();
+		}
+	}
+	
+	// Fly/free movement is accelerated by `update`, orbit moves the target directly
+	pub fn processMovement(&mut self, dir: Movement, dt: f32) {
+		self.processMovementAxis(dir, 1.0, dt);
+	}
+	
+	// `amount` 0..1 for analog input, e.g. half tilted stick moves at half speed
+	pub fn processMovementAxis(&mut self, dir: Movement, amount: f32, dt: f32) {
+		if self.mode == CameraMode::Orbit {
+			self.orbitMove(dir, dt * amount);
+			return;
+		}
+		self.queueMovement(dir, amount);
+	}
+	
+	// Applied (and smoothed) by `update`
+	pub fn processMouseMovement(&mut self, xo: f32, yo: f32, constrainPitch: bool) {
+		self.queueLook(xo * self.sensitivity, yo * self.sensitivity, constrainPitch);
+	}
+	
+	// `xo`/`yo` in degrees
+	fn applyLook(&mut self, xo: f32, yo: f32, constrainPitch: bool) {
+		if self.mode == CameraMode::Free {
+			self.freeRotate(xo, yo);
+			return;
+		}
+		
+		self.yaw += xo;
+		self.pitch += yo;
+		
+		self.yaw = self.yaw % 360.0;
+		
+		if constrainPitch {
+			self.pitch = self.pitch.clamp(-89.0, 89.0);
+		}
+		
+		self.updateVectors();
+		if self.mode == CameraMode::Orbit {
+			self.updateOrbitPosition();
+		}
+	}
+	
+	pub fn processMouseScroll(&mut self, yo: f32) {
+		// Distance has no effect on orthographic, zoom in every mode
+		if let Projection::Orthographic { zoom, pixelPerfect, .. } = &mut self.projection {
+			if yo == 0.0 {
+				return;
+			}
+			*zoom = if *pixelPerfect {
+				(zoom.round() + yo.signum()).clamp(1.0, 16.0)
+			} else {
+				(*zoom * (1.0 + yo * 0.1)).clamp(0.01, 100.0)
+			};
+			return;
+		}
+		if self.mode == CameraMode::Orbit {
+			self.orbitZoom(yo);
+			return;
+		}
+		self.fov = (self.fov - yo).clamp(1.0, 45.0);
+	}
+	
+	fn updateVectors(&mut self) {
+		if self.mode == CameraMode::Free {
+			self.syncFromOrientation();
+			return;
+		}
+		let front = Vec3 {
+			x: self.yaw.to_radians().cos() * self.pitch.to_radians().cos(),
+			y: self.pitch.to_radians().sin(),
+			z: self.yaw.to_radians().sin() * self.pitch.to_radians().cos(),
+		};
+		self.front = front;
+		self.right = self.front.cross(self.worldUp).normalize();
+		self.up = self.right.cross(self.front).normalize();
+	}
+}
+
@@ -0,0 +1,194 @@
+#![allow(non_snake_case)]
+
+use glam::{Quat, Vec2, Vec3};
+use crate::camera::{Camera, CameraMode, Movement};
+
+/*
+ * Velocity based movement and look smoothing, input is queued by `processMovement`/`processMouseMovement`
+ * and applied by `Camera::update` once per frame.
+ *
+ * Rates are per second and framerate independent, 0 means instant.
+ */
+#[derive(Copy, Clone)]
+pub struct MotionController {
+	pub velocity: Vec3,
+	// How fast velocity reaches the target speed while moving
+	pub acceleration: f32,
+	// How fast velocity decays without input
+	pub damping: f32,
+	pub sprintMultiplier: f32,
+	pub sprinting: bool,
+	// Time constant in seconds, 0 disables smoothing
+	pub mouseSmoothing: f32,
+
+	wishDir: Vec3,
+	pendingLook: Vec2,
+	constrainPitch: bool,
+	transition: Option<Transition>,
+}
+
+impl Default for MotionController {
+	fn default() -> MotionController {
+		MotionController {
+			velocity: Vec3::ZERO,
+			acceleration: 10.0,
+			damping: 8.0,
+			sprintMultiplier: 3.0,
+			sprinting: false,
+			mouseSmoothing: 0.0,
+
+			wishDir: Vec3::ZERO,
+			pendingLook: Vec2::ZERO,
+			constrainPitch: true,
+			transition: None,
+		}
+	}
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CameraPose {
+	pub pos: Vec3,
+	pub orientation: Quat,
+}
+
+#[allow(dead_code)]
+impl CameraPose {
+	// `t` 0..1, position is lerped and orientation slerped
+	pub fn lerp(&self, other: &CameraPose, t: f32) -> CameraPose {
+		CameraPose {
+			pos: self.pos.lerp(other.pos, t),
+			orientation: self.orientation.slerp(other.orientation, t),
+		}
+	}
+}
+
+#[derive(Copy, Clone)]
+struct Transition {
+	from: CameraPose,
+	to: CameraPose,
+	duration: f32,
+	elapsed: f32,
+}
+
+// Fraction of the remaining distance covered this frame for an exponential approach
+pub(super) fn approach(rate: f32, dt: f32) -> f32 {
+	if rate <= 0.0 {
+		1.0
+	} else {
+		1.0 - (-rate * dt).exp()
+	}
+}
+
+#[allow(dead_code)]
+impl Camera {
+	// Call once per frame after input has been processed
+	pub fn update(&mut self, dt: f32) {
+		if self.updateTransition(dt) {
+			return;
+		}
+
+		// Look
+		let look = self.motion.pendingLook;
+		let applied = if self.motion.mouseSmoothing > 0.0 {
+			look * (1.0 - (-dt / self.motion.mouseSmoothing).exp())
+		} else {
+			look
+		};
+		self.motion.pendingLook -= applied;
+		if applied != Vec2::ZERO {
+			self.applyLook(applied.x, applied.y, self.motion.constrainPitch);
+		}
+
+		// Movement, orbit moves its target directly. Diagonals are capped to full speed, analog stays below it
+		let wish = self.motion.wishDir.clamp_length_max(1.0);
+		self.motion.wishDir = Vec3::ZERO;
+		if self.mode == CameraMode::Orbit {
+			self.motion.velocity = Vec3::ZERO;
+			return;
+		}
+		let motion = &mut self.motion;
+		if wish != Vec3::ZERO {
+			let speed = if motion.sprinting { self.speed * motion.sprintMultiplier } else { self.speed };
+			motion.velocity += (wish * speed - motion.velocity) * approach(motion.acceleration, dt);
+		} else {
+			motion.velocity -= motion.velocity * approach(motion.damping, dt);
+			if motion.velocity.length_squared() < 1e-6 {
+				motion.velocity = Vec3::ZERO;
+			}
+		}
+		self.pos += self.motion.velocity * dt;
+	}
+
+	pub fn getPose(&self) -> CameraPose {
+		CameraPose {
+			pos: self.pos,
+			orientation: self.getOrientation(),
+		}
+	}
+
+	// Roll is only kept in free mode
+	pub fn setPose(&mut self, pose: &CameraPose) {
+		self.pos = pose.pos;
+		self.setOrientation(pose.orientation);
+		if self.mode == CameraMode::Orbit {
+			self.orbit.target = self.pos + self.front * self.orbit.distance;
+		}
+	}
+
+	// Smoothly moves to `pose` over `duration` seconds, input is ignored until it finishes
+	pub fn transitionTo(&mut self, pose: CameraPose, duration: f32) {
+		self.motion.velocity = Vec3::ZERO;
+		self.motion.pendingLook = Vec2::ZERO;
+		if duration <= 0.0 {
+			self.setPose(&pose);
+			return;
+		}
+		self.motion.transition = Some(Transition {
+			from: self.getPose(),
+			to: pose,
+			duration,
+			elapsed: 0.0,
+		});
+	}
+
+	pub fn isTransitioning(&self) -> bool {
+		self.motion.transition.is_some()
+	}
+
+	pub fn cancelTransition(&mut self) {
+		self.motion.transition = None;
+	}
+
+	pub(super) fn queueMovement(&mut self, dir: Movement, amount: f32) {
+		let up = if self.mode == CameraMode::Free { self.up } else { self.worldUp };
+		self.motion.wishDir += match dir {
+			Movement::Up => up,
+			Movement::Down => -up,
+			Movement::Forward => self.front,
+			Movement::Backward => -self.front,
+			Movement::Left => -self.right,
+			Movement::Right => self.right,
+		} * amount;
+	}
+
+	pub(super) fn queueLook(&mut self, xo: f32, yo: f32, constrainPitch: bool) {
+		self.motion.pendingLook += Vec2::new(xo, yo);
+		self.motion.constrainPitch = constrainPitch;
+	}
+
+	// Returns true while a transition owns the camera
+	fn updateTransition(&mut self, dt: f32) -> bool {
+		let Some(mut transition) = self.motion.transition else {
+			return false;
+		};
+		transition.elapsed += dt;
+		let t = (transition.elapsed / transition.duration).min(1.0);
+		// Smoothstep, eases in and out
+		let eased = t * t * (3.0 - 2.0 * t);
+		self.setPose(&transition.from.lerp(&transition.to, eased));
+		self.motion.transition = if t < 1.0 { Some(transition) } else { None };
+		self.motion.wishDir = Vec3::ZERO;
+		self.motion.pendingLook = Vec2::ZERO;
+		true
+	}
+}
@@ -0,0 +1,79 @@
+#![allow(non_snake_case)]
+
+use glam::Vec3;
+use crate::camera::{Camera, CameraMode, Movement};
+
+/*
+ * Orbit/arcball state, the camera's yaw and pitch are reused as the angles around `target`
+ */
+#[derive(Copy, Clone)]
+pub struct OrbitController {
+	pub target: Vec3,
+	pub distance: f32,
+	pub minDistance: f32,
+	pub maxDistance: f32,
+	// Fraction of the distance zoomed per scroll step
+	pub zoomSpeed: f32,
+	// Pan per pixel, scaled by distance
+	pub panSpeed: f32,
+}
+
+impl Default for OrbitController {
+	fn default() -> OrbitController {
+		OrbitController {
+			target: Vec3::ZERO,
+			distance: 5.0,
+			minDistance: 0.1,
+			maxDistance: 1000.0,
+			zoomSpeed: 0.1,
+			panSpeed: 0.002,
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl Camera {
+	pub fn orbitZoom(&mut self, yo: f32) {
+		let orbit = &mut self.orbit;
+		orbit.distance = (orbit.distance * (1.0 - yo * orbit.zoomSpeed)).clamp(orbit.minDistance, orbit.maxDistance);
+		self.updateOrbitPosition();
+	}
+
+	// Moves the target in the view plane, `xo`/`yo` in pixels
+	pub fn orbitPan(&mut self, xo: f32, yo: f32) {
+		let scale = self.orbit.distance * self.orbit.panSpeed;
+		self.orbit.target += (-self.right * xo + self.up * yo) * scale;
+		self.updateOrbitPosition();
+	}
+
+	// Keyboard pans the target, forward/backward zoom
+	pub(super) fn orbitMove(&mut self, dir: Movement, dt: f32) {
+		let speed = self.speed * dt;
+		match dir {
+			Movement::Up => self.orbit.target += self.up * speed,
+			Movement::Down => self.orbit.target -= self.up * speed,
+			Movement::Left => self.orbit.target -= self.right * speed,
+			Movement::Right => self.orbit.target += self.right * speed,
+			Movement::Forward => self.orbit.distance = (self.orbit.distance - speed).max(self.orbit.minDistance),
+			Movement::Backward => self.orbit.distance = (self.orbit.distance + speed).min(self.orbit.maxDistance),
+		}
+		self.updateOrbitPosition();
+	}
+
+	// Centers the target on the box and backs off until it fits the vertical fov
+	pub fn frameBounds(&mut self, min: Vec3, max: Vec3) {
+		let center = (min + max) * 0.5;
+		let radius = ((max - min) * 0.5).length().max(0.001);
+		self.orbit.target = center;
+		self.orbit.distance = (radius / (self.fov.to_radians() * 0.5).sin()).clamp(self.orbit.minDistance, self.orbit.maxDistance);
+		if self.mode == CameraMode::Orbit {
+			self.updateOrbitPosition();
+		} else {
+			self.pos = center - self.front * self.orbit.distance;
+		}
+	}
+
+	pub(super) fn updateOrbitPosition(&mut self) {
+		self.pos = self.orbit.target - self.front * self.orbit.distance;
+	}
+}
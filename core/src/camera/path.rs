@@ -0,0 +1,140 @@
+#![allow(non_snake_case)]
+
+use glam::{Quat, Vec3};
+use crate::camera::{Camera, CameraPose};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PathKey {
+	// Seconds
+	pub time: f32,
+	pub pose: CameraPose,
+}
+
+/*
+ * Catmull-Rom spline through the key positions, orientations are slerped between keys.
+ * For looping paths the last key should repeat the first, neighbours wrap around for a smooth seam.
+ */
+#[derive(Clone, Default)]
+pub struct CameraPath {
+	pub keys: Vec<PathKey>,
+	pub looping: bool,
+}
+
+fn catmullRom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+	let t2 = t * t;
+	let t3 = t2 * t;
+	0.5 * (2.0 * p1 + (p2 - p0) * t + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2 + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+#[allow(dead_code)]
+impl CameraPath {
+	pub fn new(looping: bool) -> CameraPath {
+		CameraPath {
+			keys: Vec::new(),
+			looping,
+		}
+	}
+
+	// Keeps keys sorted by time
+	pub fn addKey(&mut self, time: f32, pos: Vec3, orientation: Quat) {
+		let index = self.keys.partition_point(|key| key.time <= time);
+		self.keys.insert(index, PathKey {
+			time,
+			pose: CameraPose { pos, orientation },
+		});
+	}
+
+	pub fn duration(&self) -> f32 {
+		match (self.keys.first(), self.keys.last()) {
+			(Some(first), Some(last)) => last.time - first.time,
+			_ => 0.0,
+		}
+	}
+
+	// `time` is relative to the first key, clamped or wrapped depending on `looping`
+	pub fn sample(&self, time: f32) -> Option<CameraPose> {
+		let count = self.keys.len();
+		let first = self.keys.first()?;
+		let duration = self.duration();
+		if count == 1 || duration <= 0.0 {
+			return Some(first.pose);
+		}
+
+		let time = first.time + if self.looping { time.rem_euclid(duration) } else { time.clamp(0.0, duration) };
+		let i = (self.keys.partition_point(|key| key.time <= time).max(1) - 1).min(count - 2);
+		let (k1, k2) = (&self.keys[i], &self.keys[i + 1]);
+		let span = k2.time - k1.time;
+		let t = if span > 0.0 { ((time - k1.time) / span).clamp(0.0, 1.0) } else { 0.0 };
+
+		// Endpoints are duplicated when not looping, the last key repeats the first when looping
+		let p0 = if i > 0 {
+			self.keys[i - 1].pose.pos
+		} else if self.looping && count > 2 {
+			self.keys[count - 2].pose.pos
+		} else {
+			k1.pose.pos
+		};
+		let p3 = if i + 2 < count {
+			self.keys[i + 2].pose.pos
+		} else if self.looping && count > 2 {
+			self.keys[1].pose.pos
+		} else {
+			k2.pose.pos
+		};
+
+		Some(CameraPose {
+			pos: catmullRom(p0, k1.pose.pos, k2.pose.pos, p3, t),
+			orientation: k1.pose.orientation.slerp(k2.pose.orientation, t),
+		})
+	}
+}
+
+/*
+ * Plays a `CameraPath` on a camera, call `update` after `Camera::update` so the path wins
+ */
+#[derive(Copy, Clone)]
+pub struct PathPlayer {
+	pub time: f32,
+	pub speed: f32,
+	pub playing: bool,
+	pub finished: bool,
+}
+
+impl Default for PathPlayer {
+	fn default() -> PathPlayer {
+		PathPlayer {
+			time: 0.0,
+			speed: 1.0,
+			playing: false,
+			finished: false,
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl PathPlayer {
+	pub fn play(&mut self) {
+		self.time = 0.0;
+		self.playing = true;
+		self.finished = false;
+	}
+
+	pub fn stop(&mut self) {
+		self.playing = false;
+	}
+
+	pub fn update(&mut self, path: &CameraPath, camera: &mut Camera, dt: f32) {
+		if !self.playing {
+			return;
+		}
+		self.time += dt * self.speed;
+		if !path.looping && self.time >= path.duration() {
+			self.time = path.duration();
+			self.playing = false;
+			self.finished = true;
+		}
+		if let Some(pose) = path.sample(self.time) {
+			camera.setPose(&pose);
+		}
+	}
+}
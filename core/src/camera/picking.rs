@@ -0,0 +1,55 @@
+#![allow(non_snake_case)]
+
+use glam::{Vec2, Vec3};
+use crate::camera::{Camera, CameraPose};
+use crate::geometry::{Frustum, Ray};
+
+/*
+ * Screen positions are in pixels with origin top-left (winit cursor coordinates) and use `viewportSize`
+ */
+#[allow(dead_code)]
+impl Camera {
+	pub fn getAspect(&self) -> f32 {
+		self.viewportSize.x / self.viewportSize.y
+	}
+
+	// `depth` 0 is the near plane, 1 the far plane
+	pub fn unproject(&self, screenPos: Vec2, depth: f32) -> Vec3 {
+		let (near, far) = self.projection.depthRange(self.clipDepth);
+		let ndc = Vec3::new(
+			screenPos.x / self.viewportSize.x * 2.0 - 1.0,
+			1.0 - screenPos.y / self.viewportSize.y * 2.0,
+			near + (far - near) * depth,
+		);
+		self.getViewProjectionMatrix(self.getAspect()).inverse().project_point3(ndc)
+	}
+
+	// Starts on the near plane
+	pub fn screenPointToRay(&self, screenPos: Vec2) -> Ray {
+		let near = self.unproject(screenPos, 0.0);
+		let far = self.unproject(screenPos, 1.0);
+		Ray::new(near, far - near)
+	}
+
+	pub fn getFrustum(&self) -> Frustum {
+		Frustum::fromMatrix(&self.getViewProjectionMatrix(self.getAspect()), self.projection.depthRange(self.clipDepth))
+	}
+
+	// What `getInterpolatedViewProjectionMatrix` sees, cull with this when rendering between ticks
+	pub fn getInterpolatedFrustum(&self, aspect: f32, previous: &CameraPose, alpha: f32) -> Frustum {
+		Frustum::fromMatrix(&self.getInterpolatedViewProjectionMatrix(aspect, previous, alpha), self.projection.depthRange(self.clipDepth))
+	}
+
+	// None if the point is behind the camera
+	pub fn worldToScreen(&self, point: Vec3) -> Option<Vec2> {
+		let clip = self.getViewProjectionMatrix(self.getAspect()) * point.extend(1.0);
+		if clip.w <= 0.0 {
+			return None;
+		}
+		let ndc = clip.truncate() / clip.w;
+		Some(Vec2::new(
+			(ndc.x * 0.5 + 0.5) * self.viewportSize.x,
+			(0.5 - ndc.y * 0.5) * self.viewportSize.y,
+		))
+	}
+}
@@ -0,0 +1,168 @@
+#![allow(non_snake_case)]
+
+use glam::{Mat4, Vec2};
+
+/*
+ * Clip space depth range. GL defaults to -1..1, 0..1 needs glClipControl (GL 4.5/ARB_clip_control),
+ * see `render::enableClipControl`.
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum ClipDepth {
+	#[default]
+	NegativeOneToOne,
+	ZeroToOne,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[allow(dead_code)]
+pub enum Projection {
+	/*
+	 * Uses `Camera::fov` (vertical, degrees).
+	 * Reverse-Z maps near to depth 1 and far to 0, the depth test must be GREATER with depth cleared to 0.
+	 * It only applies with `ClipDepth::ZeroToOne`, with -1..1 the range is squeezed into 0.5..1 of the
+	 * depth buffer and the precision gain is lost.
+	 */
+	Perspective {
+		near: f32,
+		far: f32,
+		reverseZ: bool,
+	},
+	/*
+	 * `height` world units fit the viewport vertically at zoom 1.
+	 * Pixel perfect ignores `height`, 1 world unit = `zoom` pixels with zoom rounded to a whole number.
+	 */
+	Orthographic {
+		height: f32,
+		zoom: f32,
+		near: f32,
+		far: f32,
+		pixelPerfect: bool,
+	},
+}
+
+impl Default for Projection {
+	fn default() -> Projection {
+		Projection::Perspective {
+			near: 0.1,
+			far: 100.0,
+			reverseZ: false,
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl Projection {
+	pub fn orthographic(height: f32) -> Projection {
+		Projection::Orthographic {
+			height,
+			zoom: 1.0,
+			near: -100.0,
+			far: 100.0,
+			pixelPerfect: false,
+		}
+	}
+
+	pub fn pixelPerfect(zoom: f32) -> Projection {
+		Projection::Orthographic {
+			height: 0.0,
+			zoom,
+			near: -100.0,
+			far: 100.0,
+			pixelPerfect: true,
+		}
+	}
+
+	// `viewportSize` in pixels is only used by pixel perfect orthographic
+	pub fn getMatrix(&self, fov: f32, aspect: f32, viewportSize: Vec2, clipDepth: ClipDepth) -> Mat4 {
+		match *self {
+			Projection::Perspective { near, far, .. } => {
+				if self.isReverseZ(clipDepth) {
+					Mat4::perspective_rh(fov.to_radians(), aspect, far, near)
+				} else if clipDepth == ClipDepth::ZeroToOne {
+					Mat4::perspective_rh(fov.to_radians(), aspect, near, far)
+				} else {
+					Mat4::perspective_rh_gl(fov.to_radians(), aspect, near, far)
+				}
+			}
+			Projection::Orthographic { height, zoom, near, far, pixelPerfect } => {
+				let (halfWidth, halfHeight) = if pixelPerfect {
+					let zoom = zoom.round().max(1.0);
+					// Even pixel counts keep texels on pixel centers
+					let size = (viewportSize / 2.0).floor() * 2.0;
+					(size.x * 0.5 / zoom, size.y * 0.5 / zoom)
+				} else {
+					let halfHeight = height * 0.5 / zoom;
+					(halfHeight * aspect, halfHeight)
+				};
+				match clipDepth {
+					ClipDepth::NegativeOneToOne => Mat4::orthographic_rh_gl(-halfWidth, halfWidth, -halfHeight, halfHeight, near, far),
+					ClipDepth::ZeroToOne => Mat4::orthographic_rh(-halfWidth, halfWidth, -halfHeight, halfHeight, near, far),
+				}
+			}
+		}
+	}
+
+	// NDC depth of the near and far planes for the matrix `getMatrix` builds
+	pub fn depthRange(&self, clipDepth: ClipDepth) -> (f32, f32) {
+		if self.isReverseZ(clipDepth) {
+			return (1.0, 0.0);
+		}
+		match clipDepth {
+			ClipDepth::NegativeOneToOne => (-1.0, 1.0),
+			ClipDepth::ZeroToOne => (0.0, 1.0),
+		}
+	}
+
+	// Reverse-Z was asked for and the clip range allows it
+	pub fn isReverseZ(&self, clipDepth: ClipDepth) -> bool {
+		clipDepth == ClipDepth::ZeroToOne && matches!(self, Projection::Perspective { reverseZ: true, .. })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use glam::{vec3, Vec2, Vec3};
+	use crate::camera::{Camera, ClipDepth, Projection};
+
+	#[test]
+	fn depthRangeMatchesMatrix() {
+		let projections = [
+			Projection::Perspective { near: 0.5, far: 50.0, reverseZ: false },
+			Projection::Perspective { near: 0.5, far: 50.0, reverseZ: true },
+			Projection::Orthographic { height: 4.0, zoom: 1.0, near: 0.5, far: 50.0, pixelPerfect: false },
+		];
+		for clipDepth in [ClipDepth::NegativeOneToOne, ClipDepth::ZeroToOne] {
+			for projection in projections {
+				let m = projection.getMatrix(60.0, 1.5, Vec2::ONE, clipDepth);
+				let (near, far) = projection.depthRange(clipDepth);
+				assert!((m.project_point3(vec3(0.0, 0.0, -0.5)).z - near).abs() < 1e-4, "{:?} {:?}", projection, clipDepth);
+				assert!((m.project_point3(vec3(0.0, 0.0, -50.0)).z - far).abs() < 1e-4, "{:?} {:?}", projection, clipDepth);
+			}
+		}
+	}
+
+	#[test]
+	fn reverseZNeedsZeroToOne() {
+		let projection = Projection::Perspective { near: 0.1, far: 100.0, reverseZ: true };
+		assert!(projection.isReverseZ(ClipDepth::ZeroToOne));
+		assert!(!projection.isReverseZ(ClipDepth::NegativeOneToOne));
+		assert_eq!(projection.depthRange(ClipDepth::NegativeOneToOne), (-1.0, 1.0));
+	}
+
+	#[test]
+	fn frustumNearPlaneFollowsClipDepth() {
+		for clipDepth in [ClipDepth::NegativeOneToOne, ClipDepth::ZeroToOne] {
+			let camera = Camera {
+				projection: Projection::Perspective { near: 1.0, far: 10.0, reverseZ: true },
+				clipDepth,
+				..Camera::default()
+			};
+			let frustum = camera.getFrustum();
+			// Looking down -Z from the origin, between the eye and the near plane is outside
+			assert!(!frustum.containsPoint(Vec3::NEG_Z * 0.9), "{:?}", clipDepth);
+			assert!(frustum.containsPoint(Vec3::NEG_Z * 1.1), "{:?}", clipDepth);
+			assert!(frustum.containsPoint(Vec3::NEG_Z * 9.9), "{:?}", clipDepth);
+			assert!(!frustum.containsPoint(Vec3::NEG_Z * 10.1), "{:?}", clipDepth);
+		}
+	}
+}
@@ -0,0 +1,105 @@
+#![allow(non_snake_case)]
+
+use glam::{Quat, Vec3};
+use crate::camera::motion::approach;
+use crate::camera::{orientationFromEuler, Camera};
+use crate::geometry::Ray;
+
+/*
+ * Third-person spring arm, the camera sits `armLength` behind the target's pivot along `armDir`
+ * and is pulled in when the arm would pass through geometry
+ */
+#[derive(Copy, Clone)]
+pub struct FollowRig {
+	// Added to the target position, e.g. head height
+	pub pivotOffset: Vec3,
+	// In the target's local space (yaw only), +Z is behind a target facing yaw -90
+	pub armDir: Vec3,
+	pub armLength: f32,
+	// Kept between the camera and a hit
+	pub collisionMargin: f32,
+	// Rates per second, 0 is rigid
+	pub positionStiffness: f32,
+	pub armStiffness: f32,
+
+	currentLength: f32,
+	smoothedPos: Option<Vec3>,
+}
+
+impl Default for FollowRig {
+	fn default() -> FollowRig {
+		FollowRig {
+			pivotOffset: Vec3::new(0.0, 1.0, 0.0),
+			armDir: Vec3::new(0.0, 0.4, 1.0),
+			armLength: 4.0,
+			collisionMargin: 0.2,
+			positionStiffness: 10.0,
+			armStiffness: 4.0,
+
+			currentLength: 4.0,
+			smoothedPos: None,
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl FollowRig {
+	// Snaps on the next update instead of springing from the last position
+	pub fn reset(&mut self) {
+		self.currentLength = self.armLength;
+		self.smoothedPos = None;
+	}
+}
+
+#[allow(dead_code)]
+impl Camera {
+	/*
+	 * `targetYaw` in degrees using the camera's convention.
+	 * `raycast(ray, maxDistance)` returns the distance to the closest hit, return None for no collision.
+	 */
+	pub fn updateFollow(&mut self, rig: &mut FollowRig, targetPos: Vec3, targetYaw: f32, dt: f32, mut raycast: impl FnMut(&Ray, f32) -> Option<f32>) {
+		let pivot = targetPos + rig.pivotOffset;
+		let arm = Ray::new(pivot, orientationFromEuler(targetYaw, 0.0, 0.0) * rig.armDir);
+		let allowed = raycast(&arm, rig.armLength)
+			.map(|t| (t - rig.collisionMargin).max(0.0))
+			.unwrap_or(rig.armLength)
+			.min(rig.armLength);
+
+		// Shrink instantly so the camera never ends up inside geometry, extend with the spring
+		let collided = allowed < rig.currentLength;
+		if collided {
+			rig.currentLength = allowed;
+		} else {
+			rig.currentLength += (allowed - rig.currentLength) * approach(rig.armStiffness, dt);
+		}
+
+		let desired = arm.at(rig.currentLength);
+		let pos = match rig.smoothedPos {
+			Some(pos) if !collided => pos + (desired - pos) * approach(rig.positionStiffness, dt),
+			_ => desired,
+		};
+		rig.smoothedPos = Some(pos);
+		self.pos = pos;
+		self.lookAt(pivot);
+	}
+
+	pub fn lookAt(&mut self, point: Vec3) {
+		if let Some(orientation) = self.lookAtOrientation(point) {
+			self.setOrientation(orientation);
+		}
+	}
+
+	// Turns towards `point` at `rate` per second, 0 snaps
+	pub fn trackTarget(&mut self, point: Vec3, rate: f32, dt: f32) {
+		if let Some(orientation) = self.lookAtOrientation(point) {
+			self.setOrientation(self.getOrientation().slerp(orientation, approach(rate, dt)));
+		}
+	}
+
+	fn lookAtOrientation(&self, point: Vec3) -> Option<Quat> {
+		let dir = (point - self.pos).try_normalize()?;
+		let yaw = dir.z.atan2(dir.x).to_degrees();
+		let pitch = dir.y.clamp(-1.0, 1.0).asin().to_degrees();
+		Some(orientationFromEuler(yaw, pitch, 0.0))
+	}
+}
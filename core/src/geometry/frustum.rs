@@ -0,0 +1,144 @@
+#![allow(non_snake_case)]
+
+use glam::{Mat4, Vec3, Vec4};
+use crate::geometry::{Aabb, Plane, Sphere};
+
+/*
+ * Six planes facing inwards, extracted from a view-projection matrix (Gribb/Hartmann).
+ * Tests are conservative: objects near corners may pass even though they are outside.
+ */
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Frustum {
+	// Left, right, bottom, top, near, far
+	pub planes: [Plane; 6],
+}
+
+#[allow(dead_code)]
+impl Frustum {
+	/*
+	 * `depthRange` is the NDC depth of the visible volume, (-1, 1) for GL style matrices and (0, 1) for
+	 * glam's `perspective_rh`. Order doesn't matter so reverse-Z works too. For camera matrices use
+	 * `Projection::depthRange`, which follows the clip convention.
+	 */
+	pub fn fromMatrix(m: &Mat4, depthRange: (f32, f32)) -> Frustum {
+		let (row0, row1, row2, row3) = (m.row(0), m.row(1), m.row(2), m.row(3));
+		let (minZ, maxZ) = (depthRange.0.min(depthRange.1), depthRange.0.max(depthRange.1));
+		let plane = |v: Vec4| Plane::new(v.truncate(), v.w);
+		Frustum {
+			planes: [
+				plane(row3 + row0),
+				plane(row3 - row0),
+				plane(row3 + row1),
+				plane(row3 - row1),
+				plane(row2 - row3 * minZ),
+				plane(row3 * maxZ - row2),
+			],
+		}
+	}
+
+	pub fn containsPoint(&self, point: Vec3) -> bool {
+		self.planes.iter().all(|plane| plane.distance(point) >= 0.0)
+	}
+
+	pub fn intersectsSphere(&self, sphere: &Sphere) -> bool {
+		self.planes.iter().all(|plane| plane.distance(sphere.center) >= -sphere.radius)
+	}
+
+	// Tests the corner furthest along each plane's normal
+	pub fn intersectsAabb(&self, aabb: &Aabb) -> bool {
+		self.planes.iter().all(|plane| {
+			let corner = Vec3::select(plane.normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+			plane.distance(corner) >= 0.0
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use glam::{vec3, Mat4, Vec3};
+	use crate::geometry::{Aabb, Frustum, Sphere};
+
+	// Camera at the origin looking down -Z, 90 degree fov, near 1, far 10
+	fn perspective(reverseZ: bool) -> Frustum {
+		let projection = if reverseZ {
+			Mat4::perspective_rh(90f32.to_radians(), 1.0, 10.0, 1.0)
+		} else {
+			Mat4::perspective_rh(90f32.to_radians(), 1.0, 1.0, 10.0)
+		};
+		Frustum::fromMatrix(&projection, if reverseZ { (1.0, 0.0) } else { (0.0, 1.0) })
+	}
+
+	#[test]
+	fn pointsInsideAndOutside() {
+		let frustum = perspective(false);
+		assert!(frustum.containsPoint(vec3(0.0, 0.0, -5.0)));
+		assert!(!frustum.containsPoint(vec3(0.0, 0.0, 5.0)));
+		assert!(!frustum.containsPoint(vec3(0.0, 0.0, -0.5)));
+		assert!(!frustum.containsPoint(vec3(0.0, 0.0, -11.0)));
+		assert!(!frustum.containsPoint(vec3(6.0, 0.0, -5.0)));
+		assert!(!frustum.containsPoint(vec3(0.0, -6.0, -5.0)));
+	}
+
+	#[test]
+	fn pointsJustInsidePlanes() {
+		let frustum = perspective(false);
+		assert!(frustum.containsPoint(vec3(0.0, 0.0, -1.0 - 1e-4)));
+		assert!(frustum.containsPoint(vec3(0.0, 0.0, -10.0 + 1e-3)));
+		assert!(frustum.containsPoint(vec3(5.0 - 1e-4, 0.0, -5.0)));
+	}
+
+	#[test]
+	fn reverseZMatchesStandard() {
+		let standard = perspective(false);
+		let reverse = perspective(true);
+		for point in [vec3(0.0, 0.0, -5.0), vec3(0.0, 0.0, -0.5), vec3(0.0, 0.0, -11.0), vec3(4.0, 3.0, -6.0), vec3(2.0, 0.0, 3.0)] {
+			assert_eq!(standard.containsPoint(point), reverse.containsPoint(point), "{:?}", point);
+		}
+	}
+
+	#[test]
+	fn orthographicGl() {
+		let projection = Mat4::orthographic_rh_gl(-2.0, 2.0, -1.0, 1.0, 0.0, 10.0);
+		let frustum = Frustum::fromMatrix(&projection, (-1.0, 1.0));
+		assert!(frustum.containsPoint(vec3(1.9, 0.9, -9.9)));
+		assert!(!frustum.containsPoint(vec3(2.1, 0.0, -5.0)));
+		assert!(!frustum.containsPoint(vec3(0.0, 0.0, 0.1)));
+	}
+
+	#[test]
+	fn spheres() {
+		let frustum = perspective(false);
+		assert!(frustum.intersectsSphere(&Sphere::new(vec3(0.0, 0.0, -5.0), 1.0)));
+		// Center outside but touching through the near plane
+		assert!(frustum.intersectsSphere(&Sphere::new(vec3(0.0, 0.0, 0.0), 1.5)));
+		assert!(!frustum.intersectsSphere(&Sphere::new(vec3(0.0, 0.0, 0.0), 0.5)));
+		assert!(!frustum.intersectsSphere(&Sphere::new(vec3(20.0, 0.0, -5.0), 1.0)));
+		// Larger than the frustum
+		assert!(frustum.intersectsSphere(&Sphere::new(vec3(0.0, 0.0, -5.0), 100.0)));
+	}
+
+	#[test]
+	fn aabbs() {
+		let frustum = perspective(false);
+		assert!(frustum.intersectsAabb(&Aabb::new(vec3(-1.0, -1.0, -6.0), vec3(1.0, 1.0, -4.0))));
+		// Straddles the right plane
+		assert!(frustum.intersectsAabb(&Aabb::new(vec3(4.0, -1.0, -6.0), vec3(8.0, 1.0, -4.0))));
+		// Behind the camera
+		assert!(!frustum.intersectsAabb(&Aabb::new(vec3(-1.0, -1.0, 1.0), vec3(1.0, 1.0, 3.0))));
+		// Beyond far
+		assert!(!frustum.intersectsAabb(&Aabb::new(vec3(-1.0, -1.0, -20.0), vec3(1.0, 1.0, -12.0))));
+		// Encloses the whole frustum
+		assert!(frustum.intersectsAabb(&Aabb::new(Vec3::splat(-100.0), Vec3::splat(100.0))));
+		// Degenerate box (a point)
+		assert!(frustum.intersectsAabb(&Aabb::new(vec3(0.0, 0.0, -5.0), vec3(0.0, 0.0, -5.0))));
+	}
+
+	#[test]
+	fn translatedView() {
+		let view = Mat4::look_at_rh(vec3(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y);
+		let projection = Mat4::perspective_rh(90f32.to_radians(), 1.0, 1.0, 10.0);
+		let frustum = Frustum::fromMatrix(&(projection * view), (0.0, 1.0));
+		assert!(frustum.containsPoint(Vec3::ZERO));
+		assert!(!frustum.containsPoint(vec3(0.0, 0.0, 6.0)));
+	}
+}
@@ -0,0 +1,9 @@
+#![allow(non_snake_case)]
+
+mod frustum;
+mod ray;
+mod shapes;
+
+pub use self::frustum::Frustum;
+pub use self::ray::Ray;
+pub use self::shapes::{Aabb, Plane, Sphere};
@@ -0,0 +1,143 @@
+#![allow(non_snake_case)]
+
+use glam::Vec3;
+use crate::geometry::{Aabb, Plane, Sphere};
+
+/*
+ * Intersections return the distance `t` along the ray to the first hit in front of the origin,
+ * use `at(t)` for the point
+ */
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Ray {
+	pub origin: Vec3,
+	// Unit length
+	pub dir: Vec3,
+}
+
+#[allow(dead_code)]
+impl Ray {
+	pub fn new(origin: Vec3, dir: Vec3) -> Ray {
+		Ray {
+			origin,
+			dir: dir.normalize(),
+		}
+	}
+
+	pub fn at(&self, t: f32) -> Vec3 {
+		self.origin + self.dir * t
+	}
+
+	// Hits from both sides
+	pub fn intersectPlane(&self, plane: &Plane) -> Option<f32> {
+		let denom = plane.normal.dot(self.dir);
+		if denom.abs() < 1e-6 {
+			return None;
+		}
+		let t = -plane.distance(self.origin) / denom;
+		(t >= 0.0).then_some(t)
+	}
+
+	// Slab test, 0 if the origin is inside
+	pub fn intersectAabb(&self, aabb: &Aabb) -> Option<f32> {
+		let (mut tMin, mut tMax) = (f32::NEG_INFINITY, f32::INFINITY);
+		for axis in 0..3 {
+			let (origin, dir) = (self.origin[axis], self.dir[axis]);
+			let (min, max) = (aabb.min[axis], aabb.max[axis]);
+			// Parallel to the slab, 0 * inf would be NaN for an origin on a face
+			if dir == 0.0 {
+				if origin < min || origin > max {
+					return None;
+				}
+				continue;
+			}
+			let (t1, t2) = ((min - origin) / dir, (max - origin) / dir);
+			tMin = tMin.max(t1.min(t2));
+			tMax = tMax.min(t1.max(t2));
+		}
+		if tMax < tMin.max(0.0) {
+			return None;
+		}
+		Some(tMin.max(0.0))
+	}
+
+	// 0 if the origin is inside
+	pub fn intersectSphere(&self, sphere: &Sphere) -> Option<f32> {
+		let toCenter = sphere.center - self.origin;
+		let projected = toCenter.dot(self.dir);
+		let distSq = toCenter.length_squared() - projected * projected;
+		let radiusSq = sphere.radius * sphere.radius;
+		if distSq > radiusSq {
+			return None;
+		}
+		let half = (radiusSq - distSq).sqrt();
+		let (t0, t1) = (projected - half, projected + half);
+		if t1 < 0.0 {
+			return None;
+		}
+		Some(t0.max(0.0))
+	}
+
+	// Möller–Trumbore, hits both faces
+	pub fn intersectTriangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<f32> {
+		let edge1 = b - a;
+		let edge2 = c - a;
+		let p = self.dir.cross(edge2);
+		let det = edge1.dot(p);
+		if det.abs() < 1e-8 {
+			return None;
+		}
+		let invDet = 1.0 / det;
+		let s = self.origin - a;
+		let u = s.dot(p) * invDet;
+		if !(0.0..=1.0).contains(&u) {
+			return None;
+		}
+		let q = s.cross(edge1);
+		let v = self.dir.dot(q) * invDet;
+		if v < 0.0 || u + v > 1.0 {
+			return None;
+		}
+		let t = edge2.dot(q) * invDet;
+		(t >= 0.0).then_some(t)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use glam::{vec3, Vec3};
+	use crate::geometry::{Aabb, Ray, Sphere};
+
+	fn unitBox() -> Aabb {
+		Aabb::new(Vec3::splat(-1.0), Vec3::ONE)
+	}
+
+	#[test]
+	fn rayHitsAabb() {
+		let aabb = unitBox();
+		assert_eq!(Ray::new(vec3(0.0, 0.0, 5.0), Vec3::NEG_Z).intersectAabb(&aabb), Some(4.0));
+		assert_eq!(Ray::new(vec3(0.0, 3.0, 5.0), Vec3::NEG_Z).intersectAabb(&aabb), None);
+		// Behind the origin
+		assert_eq!(Ray::new(vec3(0.0, 0.0, 5.0), Vec3::Z).intersectAabb(&aabb), None);
+		assert_eq!(Ray::new(Vec3::ZERO, vec3(1.0, 1.0, 0.0)).intersectAabb(&aabb), Some(0.0));
+	}
+
+	#[test]
+	fn axisParallelRayOnAFaceHits() {
+		let aabb = unitBox();
+		// Grazes the x = -1 face and the y = 1 edge
+		assert_eq!(Ray::new(vec3(-1.0, 0.0, 5.0), Vec3::NEG_Z).intersectAabb(&aabb), Some(4.0));
+		assert_eq!(Ray::new(vec3(-1.0, 1.0, 5.0), Vec3::NEG_Z).intersectAabb(&aabb), Some(4.0));
+		assert_eq!(Ray::new(vec3(-1.001, 0.0, 5.0), Vec3::NEG_Z).intersectAabb(&aabb), None);
+	}
+
+	#[test]
+	fn rayHitsSphere() {
+		let sphere = Sphere::new(Vec3::ZERO, 1.0);
+		assert_eq!(Ray::new(vec3(0.0, 0.0, 5.0), Vec3::NEG_Z).intersectSphere(&sphere), Some(4.0));
+		assert_eq!(Ray::new(vec3(0.0, 2.0, 5.0), Vec3::NEG_Z).intersectSphere(&sphere), None);
+		assert_eq!(Ray::new(vec3(0.0, 0.0, 5.0), Vec3::Z).intersectSphere(&sphere), None);
+		assert_eq!(Ray::new(vec3(0.0, 0.5, 0.0), Vec3::X).intersectSphere(&sphere), Some(0.0));
+		// Tangent
+		assert_eq!(Ray::new(vec3(0.0, 1.0, 5.0), Vec3::NEG_Z).intersectSphere(&sphere), Some(5.0));
+	}
+}
@@ -0,0 +1,102 @@
+#![allow(non_snake_case)]
+
+use glam::Vec3;
+
+/*
+ * Points `p` on the plane satisfy `normal.dot(p) + d == 0`, `normal` is unit length
+ */
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Plane {
+	pub normal: Vec3,
+	pub d: f32,
+}
+
+#[allow(dead_code)]
+impl Plane {
+	pub fn new(normal: Vec3, d: f32) -> Plane {
+		let length = normal.length();
+		Plane {
+			normal: normal / length,
+			d: d / length,
+		}
+	}
+
+	pub fn fromPointNormal(point: Vec3, normal: Vec3) -> Plane {
+		let normal = normal.normalize();
+		Plane {
+			normal,
+			d: -normal.dot(point),
+		}
+	}
+
+	// Counter-clockwise winding faces the normal
+	pub fn fromPoints(a: Vec3, b: Vec3, c: Vec3) -> Plane {
+		Plane::fromPointNormal(a, (b - a).cross(c - a))
+	}
+
+	// Signed, positive on the normal's side
+	pub fn distance(&self, point: Vec3) -> f32 {
+		self.normal.dot(point) + self.d
+	}
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Aabb {
+	pub min: Vec3,
+	pub max: Vec3,
+}
+
+#[allow(dead_code)]
+impl Aabb {
+	pub fn new(min: Vec3, max: Vec3) -> Aabb {
+		Aabb {
+			min: min.min(max),
+			max: min.max(max),
+		}
+	}
+
+	pub fn fromPoints(points: &[Vec3]) -> Option<Aabb> {
+		let first = *points.first()?;
+		Some(points.iter().fold(Aabb { min: first, max: first }, |aabb, &p| Aabb {
+			min: aabb.min.min(p),
+			max: aabb.max.max(p),
+		}))
+	}
+
+	pub fn center(&self) -> Vec3 {
+		(self.min + self.max) * 0.5
+	}
+
+	// Half size
+	pub fn extents(&self) -> Vec3 {
+		(self.max - self.min) * 0.5
+	}
+
+	pub fn contains(&self, point: Vec3) -> bool {
+		point.cmpge(self.min).all() && point.cmple(self.max).all()
+	}
+
+	pub fn union(&self, other: &Aabb) -> Aabb {
+		Aabb {
+			min: self.min.min(other.min),
+			max: self.max.max(other.max),
+		}
+	}
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Sphere {
+	pub center: Vec3,
+	pub radius: f32,
+}
+
+#[allow(dead_code)]
+impl Sphere {
+	pub fn new(center: Vec3, radius: f32) -> Sphere {
+		Sphere { center, radius }
+	}
+
+	pub fn contains(&self, point: Vec3) -> bool {
+		point.distance_squared(self.center) <= self.radius * self.radius
+	}
+}
@@ -0,0 +1,83 @@
+#![allow(non_snake_case)]
+
+use std::rc::Rc;
+use egui::{ClippedPrimitive, TexturesDelta, ViewportId};
+use winit::event::WindowEvent;
+use winit::window::Window;
+use crate::profileScope;
+use crate::render::{GuiPainter, RenderContext};
+
+/*
+ * Immediate mode GUI (egui), input comes from the runners' winit events through `onWindowEvent`
+ */
+pub struct Gui {
+	pub ctx: egui::Context,
+	state: egui_winit::State,
+	painter: GuiPainter,
+	primitives: Vec<ClippedPrimitive>,
+	texturesDelta: TexturesDelta,
+	pixelsPerPoint: f32,
+	pub enabled: bool,
+}
+
+#[allow(dead_code)]
+impl Gui {
+	pub fn new(context: Rc<dyn RenderContext>, window: &Window) -> Result<Self, String> {
+		let ctx = egui::Context::default();
+		let state = egui_winit::State::new(ctx.clone(), ViewportId::ROOT, window, Some(window.scale_factor() as f32), None, None);
+		Ok(Gui {
+			ctx,
+			state,
+			painter: GuiPainter::new(context)?,
+			primitives: Vec::new(),
+			texturesDelta: TexturesDelta::default(),
+			pixelsPerPoint: window.scale_factor() as f32,
+			enabled: true,
+		})
+	}
+
+	// Returns true if the GUI consumed the event
+	pub fn onWindowEvent(&mut self, window: &Window, event: &WindowEvent) -> bool {
+		if !self.enabled {
+			return false;
+		}
+		self.state.on_window_event(window, event).consumed
+	}
+
+	// Builds the UI for this frame, drawn later by `draw`
+	pub fn run(&mut self, window: &Window, ui: impl FnMut(&egui::Context)) {
+		profileScope!("Gui::run");
+		if !self.enabled {
+			return;
+		}
+		let input = self.state.take_egui_input(window);
+		let output = self.ctx.run(input, ui);
+		self.state.handle_platform_output(window, output.platform_output);
+
+		self.texturesDelta.append(output.textures_delta);
+		self.pixelsPerPoint = output.pixels_per_point;
+		self.primitives = self.ctx.tessellate(output.shapes, output.pixels_per_point);
+	}
+
+	pub fn draw(&mut self, screenSize: (u32, u32)) {
+		profileScope!("Gui::draw");
+		let delta = std::mem::take(&mut self.texturesDelta);
+		self.painter.updateTextures(&delta);
+		if self.enabled {
+			self.painter.paint(&self.primitives, self.pixelsPerPoint, screenSize);
+		}
+		self.painter.freeTextures(&delta);
+	}
+
+	pub fn wantsKeyboard(&self) -> bool {
+		self.enabled && self.ctx.wants_keyboard_input()
+	}
+
+	pub fn wantsPointer(&self) -> bool {
+		self.enabled && self.ctx.wants_pointer_input()
+	}
+
+	pub fn destroy(&mut self) {
+		self.painter.destroy();
+	}
+}
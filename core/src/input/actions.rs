@@ -0,0 +1,179 @@
+#![allow(non_snake_case)]
+
+use std::collections::{BTreeMap, HashMap};
+use serde::{Deserialize, Serialize};
+use winit::event::MouseButton;
+use winit::keyboard::KeyCode;
+use crate::input::{GamepadAxis, GamepadButton, InputSource};
+use crate::storage::{self, StateFormat};
+
+// A digital input
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Binding {
+	Key(KeyCode),
+	Mouse(MouseButton),
+	// Active on frames the wheel moved in that direction
+	ScrollUp,
+	ScrollDown,
+	Gamepad(GamepadButton),
+}
+
+impl Binding {
+	fn pressed(&self, source: &impl InputSource) -> bool {
+		match *self {
+			Binding::Key(key) => source.keyPressed(key),
+			Binding::Mouse(button) => source.mousePressed(button),
+			Binding::ScrollUp | Binding::ScrollDown => self.held(source),
+			Binding::Gamepad(button) => source.gamepadPressed(button),
+		}
+	}
+
+	fn held(&self, source: &impl InputSource) -> bool {
+		match *self {
+			Binding::Key(key) => source.keyHeld(key),
+			Binding::Mouse(button) => source.mouseHeld(button),
+			Binding::ScrollUp => source.scrollDiff().1 > 0.0,
+			Binding::ScrollDown => source.scrollDiff().1 < 0.0,
+			Binding::Gamepad(button) => source.gamepadHeld(button),
+		}
+	}
+
+	fn released(&self, source: &impl InputSource) -> bool {
+		match *self {
+			Binding::Key(key) => source.keyReleased(key),
+			Binding::Mouse(button) => source.mouseReleased(button),
+			Binding::ScrollUp | Binding::ScrollDown => false,
+			Binding::Gamepad(button) => source.gamepadReleased(button),
+		}
+	}
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum AxisBinding {
+	// -1, 0 or 1
+	Buttons { negative: Binding, positive: Binding },
+	Gamepad { axis: GamepadAxis, scale: f32 },
+	// Unbounded, per frame
+	MouseX { scale: f32 },
+	MouseY { scale: f32 },
+	Scroll { scale: f32 },
+}
+
+impl AxisBinding {
+	fn value(&self, source: &impl InputSource) -> f32 {
+		match *self {
+			AxisBinding::Buttons { negative, positive } => positive.held(source) as i32 as f32 - negative.held(source) as i32 as f32,
+			AxisBinding::Gamepad { axis, scale } => source.gamepadAxis(axis) * scale,
+			AxisBinding::MouseX { scale } => source.mouseDiff().0 * scale,
+			AxisBinding::MouseY { scale } => source.mouseDiff().1 * scale,
+			AxisBinding::Scroll { scale } => source.scrollDiff().1 * scale,
+		}
+	}
+}
+
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub struct ActionState {
+	pub pressed: bool,
+	pub held: bool,
+	pub released: bool,
+}
+
+/*
+ * Named actions and axes bound to any number of inputs, call `update` once per frame then query by name.
+ * Unknown names are inactive. Bindings serialize so they can live in a config file.
+ */
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct ActionMap {
+	#[serde(default)]
+	pub actions: BTreeMap<String, Vec<Binding>>,
+	#[serde(default)]
+	pub axes: BTreeMap<String, Vec<AxisBinding>>,
+
+	#[serde(skip)]
+	states: HashMap<String, ActionState>,
+	#[serde(skip)]
+	values: HashMap<String, f32>,
+}
+
+#[allow(dead_code)]
+impl ActionMap {
+	pub fn fromString(text: &str, format: StateFormat) -> Result<Self, String> {
+		storage::fromString(text, format)
+	}
+
+	// None if nothing has been saved under `key`
+	pub fn load(key: &str, format: StateFormat) -> Result<Option<Self>, String> {
+		storage::load(key, format)
+	}
+
+	pub fn save(&self, key: &str, format: StateFormat) -> Result<(), String> {
+		storage::save(key, self, format)
+	}
+
+	pub fn bind(&mut self, action: &str, binding: Binding) {
+		let bindings = self.actions.entry(action.to_string()).or_default();
+		if !bindings.contains(&binding) {
+			bindings.push(binding);
+		}
+	}
+
+	pub fn bindAxis(&mut self, axis: &str, binding: AxisBinding) {
+		self.axes.entry(axis.to_string()).or_default().push(binding);
+	}
+
+	// Removes all bindings of an action or axis
+	pub fn unbind(&mut self, name: &str) {
+		self.actions.remove(name);
+		self.axes.remove(name);
+		self.states.remove(name);
+		self.values.remove(name);
+	}
+
+	// Bindings from `other` replace ours per name, handy for user overrides on top of defaults
+	pub fn merge(&mut self, other: ActionMap) {
+		self.actions.extend(other.actions);
+		self.axes.extend(other.axes);
+	}
+
+	pub fn update(&mut self, source: &impl InputSource) {
+		for (name, bindings) in &self.actions {
+			let state = ActionState {
+				pressed: bindings.iter().any(|b| b.pressed(source)),
+				held: bindings.iter().any(|b| b.held(source)),
+				released: bindings.iter().any(|b| b.released(source)),
+			};
+			self.states.insert(name.clone(), state);
+		}
+		for (name, bindings) in &self.axes {
+			// Strongest input wins so a key and a stick don't add up past 1
+			let value = bindings.iter().map(|b| b.value(source)).fold(0.0f32, |a, b| if b.abs() > a.abs() { b } else { a });
+			self.values.insert(name.clone(), value);
+		}
+	}
+
+	// Clears this frame's state, e.g. while the GUI has focus
+	pub fn reset(&mut self) {
+		self.states.clear();
+		self.values.clear();
+	}
+
+	pub fn state(&self, action: &str) -> ActionState {
+		self.states.get(action).copied().unwrap_or_default()
+	}
+
+	pub fn pressed(&self, action: &str) -> bool {
+		self.state(action).pressed
+	}
+
+	pub fn held(&self, action: &str) -> bool {
+		self.state(action).held
+	}
+
+	pub fn released(&self, action: &str) -> bool {
+		self.state(action).released
+	}
+
+	pub fn axis(&self, axis: &str) -> f32 {
+		self.values.get(axis).copied().unwrap_or(0.0)
+	}
+}
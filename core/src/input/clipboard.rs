@@ -0,0 +1,107 @@
+#![allow(non_snake_case)]
+
+#[cfg(not(target_arch = "wasm32"))]
+use log::warn;
+#[cfg(target_arch = "wasm32")]
+use std::cell::RefCell;
+#[cfg(target_arch = "wasm32")]
+use std::rc::Rc;
+#[cfg(target_arch = "wasm32")]
+use web_sys::wasm_bindgen::{closure::Closure, JsValue};
+
+/*
+ * System clipboard, arboard on native and `navigator.clipboard` on web. Reads are asynchronous on
+ * web so pasting is a request followed by polling `takeText`.
+ */
+pub struct Clipboard {
+	#[cfg(not(target_arch = "wasm32"))]
+	native: Option<arboard::Clipboard>,
+	#[cfg(not(target_arch = "wasm32"))]
+	pending: Option<String>,
+	#[cfg(target_arch = "wasm32")]
+	pending: Rc<RefCell<Option<String>>>,
+	// Kept alive until the read resolves
+	#[cfg(target_arch = "wasm32")]
+	onRead: Option<Closure<dyn FnMut(JsValue)>>,
+}
+
+impl Default for Clipboard {
+	fn default() -> Clipboard {
+		Clipboard::new()
+	}
+}
+
+#[allow(dead_code)]
+impl Clipboard {
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn new() -> Self {
+		let native = match arboard::Clipboard::new() {
+			Ok(clipboard) => Some(clipboard),
+			Err(e) => {
+				warn!("Clipboard unavailable: {}", e);
+				None
+			}
+		};
+		Clipboard {
+			native,
+			pending: None,
+		}
+	}
+
+	#[cfg(target_arch = "wasm32")]
+	pub fn new() -> Self {
+		Clipboard {
+			pending: Rc::new(RefCell::new(None)),
+			onRead: None,
+		}
+	}
+
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn setText(&mut self, text: &str) {
+		if let Some(clipboard) = &mut self.native && let Err(e) = clipboard.set_text(text) {
+			warn!("Failed to copy: {}", e);
+		}
+	}
+
+	#[cfg(target_arch = "wasm32")]
+	pub fn setText(&mut self, text: &str) {
+		if let Some(window) = web_sys::window() {
+			// Fire and forget, fails without a user gesture or permission
+			let _ = window.navigator().clipboard().write_text(text);
+		}
+	}
+
+	// The text shows up in `takeText`, right away on native and once the browser resolves it on web
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn requestText(&mut self) {
+		if let Some(clipboard) = &mut self.native {
+			match clipboard.get_text() {
+				Ok(text) => self.pending = Some(text),
+				Err(e) => warn!("Failed to paste: {}", e),
+			}
+		}
+	}
+
+	#[cfg(target_arch = "wasm32")]
+	pub fn requestText(&mut self) {
+		let Some(window) = web_sys::window() else {
+			return;
+		};
+		let pending = self.pending.clone();
+		let onRead = Closure::<dyn FnMut(JsValue)>::new(move |value: JsValue| {
+			*pending.borrow_mut() = value.as_string();
+		});
+		let _ = window.navigator().clipboard().read_text().then(&onRead);
+		self.onRead = Some(onRead);
+	}
+
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn takeText(&mut self) -> Option<String> {
+		self.pending.take()
+	}
+
+	#[cfg(target_arch = "wasm32")]
+	pub fn takeText(&mut self) -> Option<String> {
+		self.pending.borrow_mut().take()
+	}
+}
@@ -0,0 +1,111 @@
+#![allow(non_snake_case)]
+
+use log::{info, warn};
+use winit::event::WindowEvent;
+use winit::window::{CursorGrabMode, Window};
+
+/*
+ * Hides and grabs the cursor for mouse look. Locked is preferred, Confined is the fallback where
+ * locking isn't supported (X11, Windows), Locked is the only option on macOS and the web.
+ * Capture is dropped when the window loses focus and has to be requested again.
+ */
+#[derive(Default)]
+pub struct CursorCapture {
+	mode: Option<CursorGrabMode>,
+	// Browsers only grant pointer lock during a user gesture, retried on the next click
+	#[cfg(target_arch = "wasm32")]
+	pending: bool,
+	#[cfg(target_arch = "wasm32")]
+	confirmed: bool,
+}
+
+#[allow(dead_code)]
+impl CursorCapture {
+	pub fn isCaptured(&self) -> bool {
+		self.mode.is_some()
+	}
+
+	// The grab mode in use, None if not captured
+	pub fn mode(&self) -> Option<CursorGrabMode> {
+		self.mode
+	}
+
+	pub fn capture(&mut self, window: &Window) -> Result<CursorGrabMode, String> {
+		if let Some(mode) = self.mode {
+			return Ok(mode);
+		}
+		let mode = window.set_cursor_grab(CursorGrabMode::Locked)
+			.map(|_| CursorGrabMode::Locked)
+			.or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined).map(|_| CursorGrabMode::Confined))
+			.map_err(|e| format!("Failed to grab cursor: {}", e))?;
+		window.set_cursor_visible(false);
+		self.mode = Some(mode);
+		#[cfg(target_arch = "wasm32")]
+		{
+			self.pending = false;
+			self.confirmed = false;
+		}
+		info!("Cursor captured ({:?})", mode);
+		Ok(mode)
+	}
+
+	pub fn release(&mut self, window: &Window) {
+		#[cfg(target_arch = "wasm32")]
+		{
+			self.pending = false;
+		}
+		if self.mode.take().is_none() {
+			return;
+		}
+		if let Err(e) = window.set_cursor_grab(CursorGrabMode::None) {
+			warn!("Failed to release cursor: {}", e);
+		}
+		window.set_cursor_visible(true);
+		info!("Cursor released");
+	}
+
+	// Returns whether the cursor is now captured
+	pub fn toggle(&mut self, window: &Window) -> bool {
+		if self.isCaptured() {
+			self.release(window);
+		} else if let Err(e) = self.capture(window) {
+			warn!("{}", e);
+			#[cfg(target_arch = "wasm32")]
+			{
+				self.pending = true;
+			}
+		}
+		self.isCaptured()
+	}
+
+	pub fn onWindowEvent(&mut self, window: &Window, event: &WindowEvent) {
+		match event {
+			WindowEvent::Focused(false) => self.release(window),
+			#[cfg(target_arch = "wasm32")]
+			WindowEvent::MouseInput { state: winit::event::ElementState::Pressed, .. } if self.pending || (self.mode.is_some() && !self.confirmed) => {
+				// Lock may have been refused silently, request it again
+				self.mode = None;
+				if let Err(e) = self.capture(window) {
+					warn!("{}", e);
+				}
+			},
+			_ => {}
+		}
+	}
+
+	// Picks up the browser dropping pointer lock on its own, e.g. on Escape
+	pub fn update(&mut self, window: &Window) {
+		#[cfg(target_arch = "wasm32")]
+		if self.mode.is_some() {
+			let locked = web_sys::window().and_then(|w| w.document()).and_then(|d| d.pointer_lock_element()).is_some();
+			// The lock request resolves asynchronously, only trust it going away once it was seen
+			if locked {
+				self.confirmed = true;
+			} else if self.confirmed {
+				self.release(window);
+			}
+		}
+		#[cfg(not(target_arch = "wasm32"))]
+		let _ = window;
+	}
+}
@@ -0,0 +1,232 @@
+#![allow(non_snake_case)]
+
+use serde::{Deserialize, Serialize};
+use winit::event::{DeviceEvent, ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
+use crate::input::{GamepadAxis, GamepadButton, Gamepads, InputSource, TextEvent, TouchEvent};
+
+// Browsers report ~100 pixels per wheel notch
+const PIXELS_PER_LINE: f32 = 100.0;
+
+/*
+ * Everything the app reads from input in one frame, plain data so it can be recorded and replayed
+ */
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct InputFrame {
+	// Seconds
+	pub dt: f64,
+	#[serde(default)]
+	pub keysHeld: Vec<KeyCode>,
+	#[serde(default)]
+	pub keysPressed: Vec<KeyCode>,
+	#[serde(default)]
+	pub keysReleased: Vec<KeyCode>,
+	#[serde(default)]
+	pub mouseHeld: Vec<MouseButton>,
+	#[serde(default)]
+	pub mousePressed: Vec<MouseButton>,
+	#[serde(default)]
+	pub mouseReleased: Vec<MouseButton>,
+	// Window pixels, origin top-left, None outside the window
+	#[serde(default)]
+	pub cursor: Option<(f32, f32)>,
+	#[serde(default)]
+	pub cursorDiff: (f32, f32),
+	// Raw device motion, keeps working while the cursor is locked
+	#[serde(default)]
+	pub mouseDiff: (f32, f32),
+	#[serde(default)]
+	pub scrollDiff: (f32, f32),
+	#[serde(default)]
+	pub touches: Vec<TouchEvent>,
+	#[serde(default)]
+	pub text: Vec<TextEvent>,
+	#[serde(default)]
+	pub gamepads: Gamepads,
+}
+
+#[allow(dead_code)]
+impl InputFrame {
+	// Input for a following tick in the same frame, held state stays and one-off events are consumed
+	pub fn carryOver(&self) -> InputFrame {
+		let mut gamepads = self.gamepads.clone();
+		gamepads.step();
+		InputFrame {
+			dt: self.dt,
+			keysHeld: self.keysHeld.clone(),
+			mouseHeld: self.mouseHeld.clone(),
+			cursor: self.cursor,
+			gamepads,
+			..InputFrame::default()
+		}
+	}
+
+	// Folds a later frame into this one, for frames that ran no tick so their presses aren't lost
+	pub fn merge(&mut self, next: InputFrame) {
+		self.dt += next.dt;
+		self.keysHeld = next.keysHeld;
+		self.keysPressed.extend(next.keysPressed);
+		self.keysReleased.extend(next.keysReleased);
+		self.mouseHeld = next.mouseHeld;
+		self.mousePressed.extend(next.mousePressed);
+		self.mouseReleased.extend(next.mouseReleased);
+		self.cursor = next.cursor;
+		self.cursorDiff = (self.cursorDiff.0 + next.cursorDiff.0, self.cursorDiff.1 + next.cursorDiff.1);
+		self.mouseDiff = (self.mouseDiff.0 + next.mouseDiff.0, self.mouseDiff.1 + next.mouseDiff.1);
+		self.scrollDiff = (self.scrollDiff.0 + next.scrollDiff.0, self.scrollDiff.1 + next.scrollDiff.1);
+		self.touches.extend(next.touches);
+		self.text.extend(next.text);
+		self.gamepads.merge(next.gamepads);
+	}
+}
+
+impl InputSource for InputFrame {
+	fn keyPressed(&self, key: KeyCode) -> bool {
+		self.keysPressed.contains(&key)
+	}
+
+	fn keyHeld(&self, key: KeyCode) -> bool {
+		self.keysHeld.contains(&key)
+	}
+
+	fn keyReleased(&self, key: KeyCode) -> bool {
+		self.keysReleased.contains(&key)
+	}
+
+	fn mousePressed(&self, button: MouseButton) -> bool {
+		self.mousePressed.contains(&button)
+	}
+
+	fn mouseHeld(&self, button: MouseButton) -> bool {
+		self.mouseHeld.contains(&button)
+	}
+
+	fn mouseReleased(&self, button: MouseButton) -> bool {
+		self.mouseReleased.contains(&button)
+	}
+
+	fn mouseDiff(&self) -> (f32, f32) {
+		self.mouseDiff
+	}
+
+	fn scrollDiff(&self) -> (f32, f32) {
+		self.scrollDiff
+	}
+
+	fn gamepadPressed(&self, button: GamepadButton) -> bool {
+		self.gamepads.pressed(button)
+	}
+
+	fn gamepadHeld(&self, button: GamepadButton) -> bool {
+		self.gamepads.held(button)
+	}
+
+	fn gamepadReleased(&self, button: GamepadButton) -> bool {
+		self.gamepads.released(button)
+	}
+
+	fn gamepadAxis(&self, axis: GamepadAxis) -> f32 {
+		self.gamepads.axis(axis)
+	}
+}
+
+fn press<T: Copy + PartialEq>(held: &mut Vec<T>, pressed: &mut Vec<T>, value: T) {
+	if !held.contains(&value) {
+		held.push(value);
+		pressed.push(value);
+	}
+}
+
+fn release<T: Copy + PartialEq>(held: &mut Vec<T>, released: &mut Vec<T>, value: T) {
+	if let Some(index) = held.iter().position(|v| *v == value) {
+		held.remove(index);
+		released.push(value);
+	}
+}
+
+/*
+ * Builds `InputFrame`s from winit events, the runner feeds it every window/device event and calls
+ * `endFrame` once per frame
+ */
+#[derive(Default)]
+pub struct InputCollector {
+	frame: InputFrame,
+	modifiers: ModifiersState,
+}
+
+#[allow(dead_code)]
+impl InputCollector {
+	pub fn processWindowEvent(&mut self, event: &WindowEvent) {
+		let frame = &mut self.frame;
+		if let Some(text) = TextEvent::fromWindowEvent(event, self.modifiers) {
+			frame.text.push(text);
+		}
+		match event {
+			WindowEvent::ModifiersChanged(modifiers) => self.modifiers = modifiers.state(),
+			WindowEvent::KeyboardInput { event, .. } => {
+				if let PhysicalKey::Code(key) = event.physical_key {
+					match event.state {
+						ElementState::Pressed => press(&mut frame.keysHeld, &mut frame.keysPressed, key),
+						ElementState::Released => release(&mut frame.keysHeld, &mut frame.keysReleased, key),
+					}
+				}
+			},
+			WindowEvent::MouseInput { state, button, .. } => match state {
+				ElementState::Pressed => press(&mut frame.mouseHeld, &mut frame.mousePressed, *button),
+				ElementState::Released => release(&mut frame.mouseHeld, &mut frame.mouseReleased, *button),
+			},
+			WindowEvent::CursorMoved { position, .. } => {
+				let cursor = (position.x as f32, position.y as f32);
+				if let Some(previous) = frame.cursor {
+					frame.cursorDiff.0 += cursor.0 - previous.0;
+					frame.cursorDiff.1 += cursor.1 - previous.1;
+				}
+				frame.cursor = Some(cursor);
+			},
+			WindowEvent::CursorLeft { .. } => frame.cursor = None,
+			WindowEvent::MouseWheel { delta, .. } => {
+				let (x, y) = match delta {
+					MouseScrollDelta::LineDelta(x, y) => (*x, *y),
+					MouseScrollDelta::PixelDelta(delta) => (delta.x as f32 / PIXELS_PER_LINE, delta.y as f32 / PIXELS_PER_LINE),
+				};
+				frame.scrollDiff.0 += x;
+				frame.scrollDiff.1 += y;
+			},
+			// Releases never arrive for keys let go while unfocused
+			WindowEvent::Focused(false) => {
+				for key in std::mem::take(&mut frame.keysHeld) {
+					frame.keysReleased.push(key);
+				}
+				for button in std::mem::take(&mut frame.mouseHeld) {
+					frame.mouseReleased.push(button);
+				}
+			},
+			_ => {
+				if let Some(touch) = TouchEvent::fromWindowEvent(event) {
+					frame.touches.push(touch);
+				}
+			},
+		}
+	}
+
+	pub fn processDeviceEvent(&mut self, event: &DeviceEvent) {
+		if let DeviceEvent::MouseMotion { delta } = event {
+			self.frame.mouseDiff.0 += delta.0 as f32;
+			self.frame.mouseDiff.1 += delta.1 as f32;
+		}
+	}
+
+	// Returns the finished frame and starts the next one, held keys and the cursor carry over
+	pub fn endFrame(&mut self, dt: f64, gamepads: &Gamepads) -> InputFrame {
+		let next = InputFrame {
+			keysHeld: self.frame.keysHeld.clone(),
+			mouseHeld: self.frame.mouseHeld.clone(),
+			cursor: self.frame.cursor,
+			..InputFrame::default()
+		};
+		let mut frame = std::mem::replace(&mut self.frame, next);
+		frame.dt = dt;
+		frame.gamepads = gamepads.clone();
+		frame
+	}
+}
@@ -0,0 +1,243 @@
+#![allow(non_snake_case)]
+
+use std::collections::BTreeMap;
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+/*
+ * Standard gamepad layout, face buttons are named by position (South is A on Xbox, Cross on PlayStation)
+ */
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub enum GamepadButton {
+	South,
+	East,
+	West,
+	North,
+	LeftBumper,
+	RightBumper,
+	LeftTrigger,
+	RightTrigger,
+	Select,
+	Start,
+	Mode,
+	LeftStick,
+	RightStick,
+	DPadUp,
+	DPadDown,
+	DPadLeft,
+	DPadRight,
+}
+
+// Sticks are -1..1 with +Y up, triggers 0..1
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub enum GamepadAxis {
+	LeftStickX,
+	LeftStickY,
+	RightStickX,
+	RightStickY,
+	LeftTrigger,
+	RightTrigger,
+}
+
+const BUTTON_COUNT: usize = GamepadButton::DPadRight as usize + 1;
+const AXIS_COUNT: usize = GamepadAxis::RightTrigger as usize + 1;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum GamepadEvent {
+	Connected { id: usize, name: String },
+	Disconnected { id: usize },
+}
+
+// Raw values as reported by the backend, deadzones are applied by `Gamepads`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GamepadState {
+	pub name: String,
+	buttons: [bool; BUTTON_COUNT],
+	previous: [bool; BUTTON_COUNT],
+	axes: [f32; AXIS_COUNT],
+}
+
+#[allow(dead_code)]
+impl GamepadState {
+	fn new(name: &str) -> GamepadState {
+		GamepadState {
+			name: name.to_string(),
+			buttons: [false; BUTTON_COUNT],
+			previous: [false; BUTTON_COUNT],
+			axes: [0.0; AXIS_COUNT],
+		}
+	}
+
+	pub fn held(&self, button: GamepadButton) -> bool {
+		self.buttons[button as usize]
+	}
+
+	pub fn pressed(&self, button: GamepadButton) -> bool {
+		self.buttons[button as usize] && !self.previous[button as usize]
+	}
+
+	pub fn released(&self, button: GamepadButton) -> bool {
+		!self.buttons[button as usize] && self.previous[button as usize]
+	}
+
+	pub fn rawAxis(&self, axis: GamepadAxis) -> f32 {
+		self.axes[axis as usize]
+	}
+}
+
+/*
+ * All connected gamepads, fed by the runner's backend (gilrs on native, the Gamepad API on web).
+ * Runners call `step` at the start of a frame then report connections, buttons and axes.
+ * Queries without an id combine every pad so any controller can drive the app.
+ */
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Gamepads {
+	pads: BTreeMap<usize, GamepadState>,
+	events: Vec<GamepadEvent>,
+	// Radial, sticks below this are 0 and the rest is rescaled to 0..1
+	pub stickDeadzone: f32,
+	pub triggerDeadzone: f32,
+}
+
+impl Default for Gamepads {
+	fn default() -> Gamepads {
+		Gamepads {
+			pads: BTreeMap::new(),
+			events: Vec::new(),
+			stickDeadzone: 0.15,
+			triggerDeadzone: 0.05,
+		}
+	}
+}
+
+fn rescale(value: f32, deadzone: f32) -> f32 {
+	if value <= deadzone {
+		0.0
+	} else {
+		((value - deadzone) / (1.0 - deadzone)).min(1.0)
+	}
+}
+
+#[allow(dead_code)]
+impl Gamepads {
+	// Backend side
+
+	pub fn step(&mut self) {
+		self.events.clear();
+		for pad in self.pads.values_mut() {
+			pad.previous = pad.buttons;
+		}
+	}
+
+	pub fn connect(&mut self, id: usize, name: &str) {
+		if self.pads.contains_key(&id) {
+			return;
+		}
+		self.pads.insert(id, GamepadState::new(name));
+		self.events.push(GamepadEvent::Connected { id, name: name.to_string() });
+	}
+
+	pub fn disconnect(&mut self, id: usize) {
+		if self.pads.remove(&id).is_some() {
+			self.events.push(GamepadEvent::Disconnected { id });
+		}
+	}
+
+	pub fn setButton(&mut self, id: usize, button: GamepadButton, down: bool) {
+		if let Some(pad) = self.pads.get_mut(&id) {
+			pad.buttons[button as usize] = down;
+		}
+	}
+
+	// Sticks -1..1 with +Y up, triggers 0..1
+	pub fn setAxis(&mut self, id: usize, axis: GamepadAxis, value: f32) {
+		if let Some(pad) = self.pads.get_mut(&id) {
+			pad.axes[axis as usize] = value;
+		}
+	}
+
+	// Takes the newer state but keeps our previous buttons and events, so presses that happened
+	// between the two still show up
+	pub fn merge(&mut self, newer: Gamepads) {
+		let older = std::mem::replace(&mut self.pads, newer.pads);
+		for (id, pad) in &mut self.pads {
+			if let Some(old) = older.get(id) {
+				pad.previous = old.previous;
+			}
+		}
+		self.events.extend(newer.events);
+		self.stickDeadzone = newer.stickDeadzone;
+		self.triggerDeadzone = newer.triggerDeadzone;
+	}
+
+	// App side
+
+	// Hot-plug events since the last `step`
+	pub fn events(&self) -> &[GamepadEvent] {
+		&self.events
+	}
+
+	pub fn get(&self, id: usize) -> Option<&GamepadState> {
+		self.pads.get(&id)
+	}
+
+	pub fn connected(&self) -> impl Iterator<Item = (usize, &GamepadState)> {
+		self.pads.iter().map(|(id, pad)| (*id, pad))
+	}
+
+	pub fn isAnyConnected(&self) -> bool {
+		!self.pads.is_empty()
+	}
+
+	pub fn held(&self, button: GamepadButton) -> bool {
+		self.pads.values().any(|pad| pad.held(button))
+	}
+
+	pub fn pressed(&self, button: GamepadButton) -> bool {
+		self.pads.values().any(|pad| pad.pressed(button))
+	}
+
+	pub fn released(&self, button: GamepadButton) -> bool {
+		self.pads.values().any(|pad| pad.released(button))
+	}
+
+	pub fn leftStick(&self, id: usize) -> Vec2 {
+		self.stick(id, GamepadAxis::LeftStickX, GamepadAxis::LeftStickY)
+	}
+
+	pub fn rightStick(&self, id: usize) -> Vec2 {
+		self.stick(id, GamepadAxis::RightStickX, GamepadAxis::RightStickY)
+	}
+
+	// With deadzone, the strongest value over all pads
+	pub fn axis(&self, axis: GamepadAxis) -> f32 {
+		self.pads.keys().map(|&id| self.padAxis(id, axis)).fold(0.0, |a, b| if b.abs() > a.abs() { b } else { a })
+	}
+
+	pub fn padAxis(&self, id: usize, axis: GamepadAxis) -> f32 {
+		match axis {
+			GamepadAxis::LeftStickX => self.leftStick(id).x,
+			GamepadAxis::LeftStickY => self.leftStick(id).y,
+			GamepadAxis::RightStickX => self.rightStick(id).x,
+			GamepadAxis::RightStickY => self.rightStick(id).y,
+			GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger => {
+				let value = self.pads.get(&id).map_or(0.0, |pad| pad.rawAxis(axis));
+				rescale(value, self.triggerDeadzone)
+			}
+		}
+	}
+
+	fn stick(&self, id: usize, x: GamepadAxis, y: GamepadAxis) -> Vec2 {
+		let Some(pad) = self.pads.get(&id) else {
+			return Vec2::ZERO;
+		};
+		let raw = Vec2::new(pad.rawAxis(x), pad.rawAxis(y));
+		let length = raw.length();
+		if length <= self.stickDeadzone {
+			return Vec2::ZERO;
+		}
+		raw / length * rescale(length, self.stickDeadzone)
+	}
+}
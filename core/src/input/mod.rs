@@ -0,0 +1,21 @@
+#![allow(non_snake_case)]
+
+mod actions;
+mod clipboard;
+mod cursor;
+mod frame;
+mod gamepad;
+mod recording;
+mod source;
+mod text;
+mod touch;
+
+pub use self::actions::{ActionMap, ActionState, AxisBinding, Binding};
+pub use self::clipboard::Clipboard;
+pub use self::cursor::CursorCapture;
+pub use self::frame::{InputCollector, InputFrame};
+pub use self::gamepad::{GamepadAxis, GamepadButton, GamepadEvent, GamepadState, Gamepads};
+pub use self::recording::{InputRecording, InputReplay, InputSession, REPLAY_DT};
+pub use self::source::InputSource;
+pub use self::text::{TextEvent, TextInput, TextKey, TextResponse};
+pub use self::touch::{TouchEvent, TouchGesture, TouchInput, TouchPoint, VirtualJoystick};
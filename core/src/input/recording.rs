@@ -0,0 +1,260 @@
+#![allow(non_snake_case)]
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use winit::event::{DeviceEvent, ElementState, KeyEvent, WindowEvent};
+use crate::input::{Gamepads, InputCollector, InputFrame};
+use crate::storage::{self, StateFormat};
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct InputRecording {
+	pub frames: Vec<InputFrame>,
+}
+
+#[allow(dead_code)]
+impl InputRecording {
+	pub fn push(&mut self, frame: InputFrame) {
+		self.frames.push(frame);
+	}
+
+	// Seconds of recorded time
+	pub fn duration(&self) -> f64 {
+		self.frames.iter().map(|f| f.dt).sum()
+	}
+
+	pub fn save(&self, key: &str, format: StateFormat) -> Result<(), String> {
+		storage::save(key, self, format)
+	}
+
+	pub fn load(key: &str, format: StateFormat) -> Result<Self, String> {
+		storage::load(key, format)?.ok_or_else(|| format!("No input recording at {}", key))
+	}
+}
+
+// Replays step this much per frame unless told otherwise, so runs don't depend on the recording machine's frame rate
+pub const REPLAY_DT: f64 = 1.0 / 60.0;
+
+// Seconds of recorded input between autosaves
+const AUTOSAVE_INTERVAL: f64 = 5.0;
+
+/*
+ * Plays a recording back frame by frame. With a fixed dt every run of the same recording
+ * steps the app identically, otherwise the recorded frame times are used.
+ */
+pub struct InputReplay {
+	recording: InputRecording,
+	index: usize,
+	pub fixedDt: Option<f64>,
+}
+
+#[allow(dead_code)]
+impl InputReplay {
+	pub fn new(recording: InputRecording, fixedDt: Option<f64>) -> Self {
+		InputReplay {
+			recording,
+			index: 0,
+			fixedDt,
+		}
+	}
+
+	// None once every frame has been played
+	pub fn nextFrame(&mut self) -> Option<InputFrame> {
+		let mut frame = self.recording.frames.get(self.index)?.clone();
+		self.index += 1;
+		if let Some(dt) = self.fixedDt {
+			frame.dt = dt;
+		}
+		Some(frame)
+	}
+
+	pub fn isFinished(&self) -> bool {
+		self.index >= self.recording.frames.len()
+	}
+
+	pub fn frameIndex(&self) -> usize {
+		self.index
+	}
+
+	pub fn frameCount(&self) -> usize {
+		self.recording.frames.len()
+	}
+}
+
+enum SessionMode {
+	Live,
+	// `unsaved` is the recorded time since the last save
+	Record { key: String, recording: InputRecording, unsaved: f64 },
+	Replay(InputReplay),
+}
+
+/*
+ * Where a runner gets each frame's input from: live winit events, live events that are also
+ * recorded to storage, or a previous recording with live input ignored. Recordings are RON.
+ */
+pub struct InputSession {
+	collector: InputCollector,
+	mode: SessionMode,
+}
+
+impl Default for InputSession {
+	fn default() -> InputSession {
+		InputSession {
+			collector: InputCollector::default(),
+			mode: SessionMode::Live,
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl InputSession {
+	pub fn record(key: &str) -> Self {
+		InputSession {
+			mode: SessionMode::Record {
+				key: key.to_string(),
+				recording: InputRecording::default(),
+				unsaved: 0.0,
+			},
+			..InputSession::default()
+		}
+	}
+
+	pub fn replay(key: &str, fixedDt: Option<f64>) -> Result<Self, String> {
+		let recording = InputRecording::load(key, StateFormat::Ron)?;
+		info!("Replaying {} frames from {}", recording.frames.len(), key);
+		Ok(InputSession {
+			mode: SessionMode::Replay(InputReplay::new(recording, fixedDt)),
+			..InputSession::default()
+		})
+	}
+
+	pub fn isRecording(&self) -> bool {
+		matches!(self.mode, SessionMode::Record { .. })
+	}
+
+	pub fn isReplaying(&self) -> bool {
+		matches!(self.mode, SessionMode::Replay(_))
+	}
+
+	// `consumed` events were used by the GUI and are dropped, except releases so nothing stays held
+	pub fn processWindowEvent(&mut self, event: &WindowEvent, consumed: bool) {
+		let release = matches!(event,
+			WindowEvent::KeyboardInput { event: KeyEvent { state: ElementState::Released, .. }, .. }
+			| WindowEvent::MouseInput { state: ElementState::Released, .. });
+		if !consumed || release {
+			self.collector.processWindowEvent(event);
+		}
+	}
+
+	pub fn processDeviceEvent(&mut self, event: &DeviceEvent) {
+		self.collector.processDeviceEvent(event);
+	}
+
+	// The input for this frame, None when a replay has run out
+	pub fn nextFrame(&mut self, dt: f64, gamepads: &Gamepads) -> Option<InputFrame> {
+		// Live events are still collected during a replay so held keys are right afterwards
+		let live = self.collector.endFrame(dt, gamepads);
+		match &mut self.mode {
+			SessionMode::Live => Some(live),
+			SessionMode::Record { recording, unsaved, .. } => {
+				*unsaved += live.dt;
+				recording.push(live.clone());
+				Some(live)
+			},
+			SessionMode::Replay(replay) => replay.nextFrame(),
+		}
+	}
+
+	// Writes what has been recorded so far, recording continues
+	pub fn saveRecording(&mut self) -> Result<(), String> {
+		if let SessionMode::Record { key, recording, unsaved } = &mut self.mode {
+			*unsaved = 0.0;
+			recording.save(key, StateFormat::Ron)?;
+			info!("Saved {} input frames to {}", recording.frames.len(), key);
+		}
+		Ok(())
+	}
+
+	// Call once per frame, saves every few seconds so a crash only loses the last few
+	pub fn autosave(&mut self) -> Result<(), String> {
+		match self.mode {
+			SessionMode::Record { unsaved, .. } if unsaved >= AUTOSAVE_INTERVAL => self.saveRecording(),
+			_ => Ok(()),
+		}
+	}
+
+	// Saves the recording, if any, and goes back to live input
+	pub fn finish(&mut self) -> Result<(), String> {
+		let result = self.saveRecording();
+		self.mode = SessionMode::Live;
+		result
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use glam::Vec3;
+	use winit::keyboard::KeyCode;
+	use crate::camera::{Camera, Movement};
+	use crate::input::{ActionMap, Binding, InputFrame, InputRecording, InputReplay, REPLAY_DT};
+	use crate::storage::{self, StateFormat};
+
+	// Walks forward for 20 frames, then looks around for 10
+	fn recording() -> InputRecording {
+		let mut recording = InputRecording::default();
+		for i in 0..30 {
+			recording.push(InputFrame {
+				dt: 0.01 + i as f64 * 0.001,
+				keysHeld: if i < 20 { vec![KeyCode::KeyW] } else { Vec::new() },
+				keysPressed: if i == 0 { vec![KeyCode::KeyW] } else { Vec::new() },
+				mouseDiff: if i < 20 { (0.0, 0.0) } else { (i as f32, -1.0) },
+				..InputFrame::default()
+			});
+		}
+		recording
+	}
+
+	// Drives a camera the way the app does and returns where it ended up
+	fn run(replay: &mut InputReplay) -> Camera {
+		let mut actions = ActionMap::default();
+		actions.bind("forward", Binding::Key(KeyCode::KeyW));
+		let mut camera = Camera::default();
+		while let Some(frame) = replay.nextFrame() {
+			actions.update(&frame);
+			if actions.held("forward") {
+				camera.processMovement(Movement::Forward, frame.dt as f32);
+			}
+			camera.processMouseMovement(frame.mouseDiff.0, frame.mouseDiff.1, true);
+			camera.update(frame.dt as f32);
+		}
+		camera
+	}
+
+	#[test]
+	fn roundTripsThroughRon() {
+		let text = storage::toString(&recording(), StateFormat::Ron).unwrap();
+		let loaded: InputRecording = storage::fromString(&text, StateFormat::Ron).unwrap();
+		assert_eq!(loaded.frames.len(), 30);
+		assert_eq!(loaded.frames[5].keysHeld, vec![KeyCode::KeyW]);
+		assert_eq!(loaded.frames[25].mouseDiff, (25.0, -1.0));
+		assert_eq!(loaded.duration(), recording().duration());
+	}
+
+	#[test]
+	fn fixedDtReplayReachesRecordedOutcome() {
+		let mut replay = InputReplay::new(recording(), Some(REPLAY_DT));
+		assert_eq!(replay.nextFrame().unwrap().dt, REPLAY_DT);
+
+		// Accelerates towards 2.5 for 20 frames facing -Z, then damps for 10 while turning
+		let camera = run(&mut InputReplay::new(recording(), Some(REPLAY_DT)));
+		assert!((camera.pos - Vec3::new(0.0, 0.0, -0.81923)).length() < 1e-4, "{}", camera.pos);
+		assert!((camera.motion.velocity.length() - 0.63548).abs() < 1e-4);
+		// -90 + 0.1 * (20 + ... + 29), 0.1 * -1 * 10
+		assert!((camera.yaw - -65.5).abs() < 1e-4, "{}", camera.yaw);
+		assert!((camera.pitch - -1.0).abs() < 1e-4, "{}", camera.pitch);
+
+		let mut replay = InputReplay::new(recording(), None);
+		run(&mut replay);
+		assert!(replay.isFinished());
+		assert_eq!(replay.frameIndex(), replay.frameCount());
+	}
+}
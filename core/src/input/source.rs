@@ -0,0 +1,35 @@
+#![allow(non_snake_case)]
+
+use winit::event::MouseButton;
+use winit::keyboard::KeyCode;
+use crate::input::{GamepadAxis, GamepadButton};
+
+/*
+ * Raw input state for one frame, lets the action map work with any backend
+ */
+#[allow(unused_variables)]
+pub trait InputSource {
+	fn keyPressed(&self, key: KeyCode) -> bool;
+	fn keyHeld(&self, key: KeyCode) -> bool;
+	fn keyReleased(&self, key: KeyCode) -> bool;
+
+	fn mousePressed(&self, button: MouseButton) -> bool;
+	fn mouseHeld(&self, button: MouseButton) -> bool;
+	fn mouseReleased(&self, button: MouseButton) -> bool;
+	// Raw motion since last frame
+	fn mouseDiff(&self) -> (f32, f32);
+	fn scrollDiff(&self) -> (f32, f32);
+
+	fn gamepadPressed(&self, button: GamepadButton) -> bool {
+		false
+	}
+	fn gamepadHeld(&self, button: GamepadButton) -> bool {
+		false
+	}
+	fn gamepadReleased(&self, button: GamepadButton) -> bool {
+		false
+	}
+	fn gamepadAxis(&self, axis: GamepadAxis) -> f32 {
+		0.0
+	}
+}
@@ -0,0 +1,224 @@
+#![allow(non_snake_case)]
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, Ime, WindowEvent};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use winit::window::Window;
+use crate::input::Clipboard;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum TextKey {
+	Backspace,
+	Delete,
+	Left,
+	Right,
+	Home,
+	End,
+	Enter,
+	Escape,
+	Copy,
+	Cut,
+	Paste,
+}
+
+// Text related window events, plain data so they can be recorded
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum TextEvent {
+	// Typed characters or IME committed text
+	Text(String),
+	// IME composition in progress, empty when it ends. Cursor is a byte range in the preedit text
+	Preedit { text: String, cursor: Option<(usize, usize)> },
+	Key(TextKey),
+}
+
+// Same filter as egui, drops control characters and the private use keys macOS sends for arrows etc.
+fn isPrintable(c: char) -> bool {
+	let privateUse = ('\u{e000}'..='\u{f8ff}').contains(&c) || ('\u{f0000}'..='\u{ffffd}').contains(&c) || ('\u{100000}'..='\u{10fffd}').contains(&c);
+	!privateUse && !c.is_ascii_control()
+}
+
+impl TextEvent {
+	pub fn fromWindowEvent(event: &WindowEvent, modifiers: ModifiersState) -> Option<TextEvent> {
+		match event {
+			WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+				// Cmd on macOS, Ctrl elsewhere
+				let command = if cfg!(target_os = "macos") { modifiers.super_key() } else { modifiers.control_key() };
+				let key = match &event.logical_key {
+					Key::Named(NamedKey::Backspace) => Some(TextKey::Backspace),
+					Key::Named(NamedKey::Delete) => Some(TextKey::Delete),
+					Key::Named(NamedKey::ArrowLeft) => Some(TextKey::Left),
+					Key::Named(NamedKey::ArrowRight) => Some(TextKey::Right),
+					Key::Named(NamedKey::Home) => Some(TextKey::Home),
+					Key::Named(NamedKey::End) => Some(TextKey::End),
+					Key::Named(NamedKey::Enter) => Some(TextKey::Enter),
+					Key::Named(NamedKey::Escape) => Some(TextKey::Escape),
+					Key::Named(NamedKey::Copy) => Some(TextKey::Copy),
+					Key::Named(NamedKey::Cut) => Some(TextKey::Cut),
+					Key::Named(NamedKey::Paste) => Some(TextKey::Paste),
+					Key::Character(c) if command && c.eq_ignore_ascii_case("c") => Some(TextKey::Copy),
+					Key::Character(c) if command && c.eq_ignore_ascii_case("x") => Some(TextKey::Cut),
+					Key::Character(c) if command && c.eq_ignore_ascii_case("v") => Some(TextKey::Paste),
+					_ => None,
+				};
+				if let Some(key) = key {
+					return Some(TextEvent::Key(key));
+				}
+				let text = event.text.as_ref()?;
+				if command || text.is_empty() || !text.chars().all(isPrintable) {
+					return None;
+				}
+				Some(TextEvent::Text(text.to_string()))
+			},
+			WindowEvent::Ime(Ime::Commit(text)) => Some(TextEvent::Text(text.clone())),
+			WindowEvent::Ime(Ime::Preedit(text, cursor)) => Some(TextEvent::Preedit { text: text.clone(), cursor: *cursor }),
+			WindowEvent::Ime(Ime::Disabled) => Some(TextEvent::Preedit { text: String::new(), cursor: None }),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct TextResponse {
+	pub changed: bool,
+	// Text when Enter was pressed, the field is cleared
+	pub submitted: Option<String>,
+	pub cancelled: bool,
+}
+
+/*
+ * Single line text field state. Only takes input while focused, focusing also enables the IME so
+ * the OS composition window shows up next to the field.
+ */
+#[derive(Default)]
+pub struct TextInput {
+	pub text: String,
+	// Byte offset into `text`
+	cursor: usize,
+	preedit: String,
+	preeditCursor: Option<(usize, usize)>,
+	focused: bool,
+	clipboard: Clipboard,
+}
+
+#[allow(dead_code)]
+impl TextInput {
+	// `pos` and `size` of the field in window pixels, origin top-left
+	pub fn focus(&mut self, window: &Window, pos: Vec2, size: Vec2) {
+		self.focused = true;
+		self.cursor = self.text.len();
+		window.set_ime_allowed(true);
+		self.setImeArea(window, pos, size);
+	}
+
+	pub fn blur(&mut self, window: &Window) {
+		self.focused = false;
+		self.preedit.clear();
+		self.preeditCursor = None;
+		window.set_ime_allowed(false);
+	}
+
+	pub fn isFocused(&self) -> bool {
+		self.focused
+	}
+
+	pub fn setImeArea(&self, window: &Window, pos: Vec2, size: Vec2) {
+		window.set_ime_cursor_area(PhysicalPosition::new(pos.x, pos.y), PhysicalSize::new(size.x, size.y));
+	}
+
+	pub fn clear(&mut self) {
+		self.text.clear();
+		self.cursor = 0;
+	}
+
+	pub fn cursor(&self) -> usize {
+		self.cursor
+	}
+
+	pub fn preedit(&self) -> &str {
+		&self.preedit
+	}
+
+	// The text with the IME composition shown at the cursor
+	pub fn displayText(&self) -> String {
+		let mut text = self.text.clone();
+		text.insert_str(self.cursor, &self.preedit);
+		text
+	}
+
+	pub fn update(&mut self, events: &[TextEvent]) -> TextResponse {
+		let mut response = TextResponse::default();
+		let pasted = self.clipboard.takeText();
+		if !self.focused {
+			return response;
+		}
+		if let Some(text) = pasted {
+			// Single line
+			self.insert(&text.replace(['\r', '\n'], " "));
+			response.changed = true;
+		}
+		for event in events {
+			match event {
+				TextEvent::Text(text) => {
+					self.insert(text);
+					response.changed = true;
+				},
+				TextEvent::Preedit { text, cursor } => {
+					self.preedit = text.clone();
+					self.preeditCursor = *cursor;
+				},
+				TextEvent::Key(key) => self.key(*key, &mut response),
+			}
+		}
+		response
+	}
+
+	fn insert(&mut self, text: &str) {
+		self.text.insert_str(self.cursor, text);
+		self.cursor += text.len();
+	}
+
+	fn previousBoundary(&self) -> usize {
+		self.text[..self.cursor].char_indices().next_back().map_or(0, |(i, _)| i)
+	}
+
+	fn nextBoundary(&self) -> usize {
+		self.text[self.cursor..].chars().next().map_or(self.cursor, |c| self.cursor + c.len_utf8())
+	}
+
+	fn key(&mut self, key: TextKey, response: &mut TextResponse) {
+		match key {
+			TextKey::Backspace if self.cursor > 0 => {
+				let start = self.previousBoundary();
+				self.text.replace_range(start..self.cursor, "");
+				self.cursor = start;
+				response.changed = true;
+			},
+			TextKey::Delete if self.cursor < self.text.len() => {
+				let end = self.nextBoundary();
+				self.text.replace_range(self.cursor..end, "");
+				response.changed = true;
+			},
+			TextKey::Left => self.cursor = self.previousBoundary(),
+			TextKey::Right => self.cursor = self.nextBoundary(),
+			TextKey::Home => self.cursor = 0,
+			TextKey::End => self.cursor = self.text.len(),
+			TextKey::Enter => {
+				response.submitted = Some(std::mem::take(&mut self.text));
+				self.cursor = 0;
+				response.changed = true;
+			},
+			TextKey::Escape => response.cancelled = true,
+			// No selection, copy and cut work on the whole field
+			TextKey::Copy => self.clipboard.setText(&self.text),
+			TextKey::Cut => {
+				self.clipboard.setText(&self.text);
+				self.clear();
+				response.changed = true;
+			},
+			TextKey::Paste => self.clipboard.requestText(),
+			_ => {},
+		}
+	}
+}
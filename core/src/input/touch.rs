@@ -0,0 +1,261 @@
+#![allow(non_snake_case)]
+
+use std::collections::BTreeMap;
+use glam::{vec2, Vec2};
+use serde::{Deserialize, Serialize};
+use winit::event::{TouchPhase, WindowEvent};
+
+// The touch related window events, plain data so they can be recorded
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum TouchEvent {
+	Touch { id: u64, phase: TouchPhase, pos: Vec2 },
+	// Trackpad gestures
+	Pinch { delta: f32 },
+	Pan { delta: Vec2 },
+}
+
+impl TouchEvent {
+	pub fn fromWindowEvent(event: &WindowEvent) -> Option<TouchEvent> {
+		match event {
+			WindowEvent::Touch(touch) => Some(TouchEvent::Touch {
+				id: touch.id,
+				phase: touch.phase,
+				pos: vec2(touch.location.x as f32, touch.location.y as f32),
+			}),
+			WindowEvent::PinchGesture { delta, .. } if delta.is_finite() => Some(TouchEvent::Pinch { delta: *delta as f32 }),
+			WindowEvent::PanGesture { delta, .. } => Some(TouchEvent::Pan { delta: vec2(delta.x, delta.y) }),
+			_ => None,
+		}
+	}
+}
+
+// Screen positions are in pixels with the origin top-left, like the cursor
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TouchPoint {
+	pub id: u64,
+	pub start: Vec2,
+	pub pos: Vec2,
+	previous: Vec2,
+	// Seconds since the touch started
+	age: f32,
+	joystick: Option<usize>,
+	// Too long, too far or part of a multi-touch gesture to count as a tap
+	tapCancelled: bool,
+}
+
+/*
+ * On-screen stick driven by a touch that starts inside its area
+ */
+#[derive(Clone, PartialEq, Debug)]
+pub struct VirtualJoystick {
+	// Center as a fraction of the viewport, (0, 0) top-left
+	pub anchor: Vec2,
+	// Pixels for full deflection
+	pub radius: f32,
+	// -1..1 on each axis, +Y up
+	pub value: Vec2,
+	touch: Option<u64>,
+}
+
+#[allow(dead_code)]
+impl VirtualJoystick {
+	pub fn new(anchor: Vec2, radius: f32) -> Self {
+		VirtualJoystick {
+			anchor,
+			radius,
+			value: Vec2::ZERO,
+			touch: None,
+		}
+	}
+
+	pub fn center(&self, viewport: Vec2) -> Vec2 {
+		self.anchor * viewport
+	}
+
+	pub fn isActive(&self) -> bool {
+		self.touch.is_some()
+	}
+
+	// Touches a bit outside the drawn ring still grab the stick
+	fn contains(&self, pos: Vec2, viewport: Vec2) -> bool {
+		pos.distance(self.center(viewport)) <= self.radius * 1.5
+	}
+}
+
+// Motion of the touches not held by a joystick since the last update
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub struct TouchGesture {
+	// One finger, pixels
+	pub drag: Vec2,
+	// Two finger midpoint, pixels
+	pub pan: Vec2,
+	// Relative change of the two finger spread, positive when spreading (zoom in)
+	pub pinch: f32,
+}
+
+/*
+ * Multi-touch tracking fed from window events, `update` once per frame turns the raw touches into
+ * joystick values, gestures and taps. Trackpad pinch/pan gestures feed the same values.
+ */
+pub struct TouchInput {
+	pub joysticks: Vec<VirtualJoystick>,
+	// Max movement in pixels and duration in seconds of a tap
+	pub tapDistance: f32,
+	pub tapTime: f32,
+
+	touches: BTreeMap<u64, TouchPoint>,
+	viewport: Vec2,
+	pendingTaps: Vec<Vec2>,
+	taps: Vec<Vec2>,
+	pendingGesture: TouchGesture,
+	gesture: TouchGesture,
+	used: bool,
+}
+
+impl Default for TouchInput {
+	fn default() -> TouchInput {
+		TouchInput {
+			joysticks: Vec::new(),
+			tapDistance: 12.0,
+			tapTime: 0.3,
+			touches: BTreeMap::new(),
+			viewport: Vec2::ZERO,
+			pendingTaps: Vec::new(),
+			taps: Vec::new(),
+			pendingGesture: TouchGesture::default(),
+			gesture: TouchGesture::default(),
+			used: false,
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl TouchInput {
+	// Returns true if the event was touch input
+	pub fn onWindowEvent(&mut self, event: &WindowEvent) -> bool {
+		match TouchEvent::fromWindowEvent(event) {
+			Some(event) => {
+				self.onTouchEvent(&event);
+				true
+			},
+			None => false,
+		}
+	}
+
+	pub fn onTouchEvent(&mut self, event: &TouchEvent) {
+		match *event {
+			TouchEvent::Touch { id, phase, pos } => match phase {
+				TouchPhase::Started => self.start(id, pos),
+				TouchPhase::Moved => {
+					if let Some(point) = self.touches.get_mut(&id) {
+						point.pos = pos;
+					}
+				},
+				TouchPhase::Ended => self.end(id, pos, true),
+				TouchPhase::Cancelled => self.end(id, pos, false),
+			},
+			TouchEvent::Pinch { delta } => self.pendingGesture.pinch += delta,
+			TouchEvent::Pan { delta } => self.pendingGesture.pan += delta,
+		}
+	}
+
+	fn start(&mut self, id: u64, pos: Vec2) {
+		self.used = true;
+		let viewport = self.viewport;
+		let joystick = self.joysticks.iter().position(|j| !j.isActive() && j.contains(pos, viewport));
+		if let Some(index) = joystick {
+			self.joysticks[index].touch = Some(id);
+		}
+		self.touches.insert(id, TouchPoint {
+			id,
+			start: pos,
+			pos,
+			previous: pos,
+			age: 0.0,
+			joystick,
+			tapCancelled: joystick.is_some(),
+		});
+	}
+
+	fn end(&mut self, id: u64, pos: Vec2, tap: bool) {
+		let Some(point) = self.touches.remove(&id) else {
+			return;
+		};
+		if let Some(index) = point.joystick && let Some(joystick) = self.joysticks.get_mut(index) {
+			joystick.touch = None;
+			joystick.value = Vec2::ZERO;
+		}
+		if tap && !point.tapCancelled && point.age <= self.tapTime && pos.distance(point.start) <= self.tapDistance {
+			self.pendingTaps.push(pos);
+		}
+	}
+
+	pub fn update(&mut self, dt: f32, viewport: Vec2) {
+		self.viewport = viewport;
+		self.gesture = std::mem::take(&mut self.pendingGesture);
+		self.taps = std::mem::take(&mut self.pendingTaps);
+
+		for joystick in &mut self.joysticks {
+			if let Some(point) = joystick.touch.and_then(|id| self.touches.get(&id)) {
+				let offset = (point.pos - joystick.center(viewport)) / joystick.radius.max(1.0);
+				joystick.value = (offset * vec2(1.0, -1.0)).clamp_length_max(1.0);
+			}
+		}
+
+		let free: Vec<&TouchPoint> = self.touches.values().filter(|p| p.joystick.is_none()).collect();
+		match free.as_slice() {
+			[a] => self.gesture.drag += a.pos - a.previous,
+			[a, b] => {
+				self.gesture.pan += (a.pos + b.pos - a.previous - b.previous) * 0.5;
+				let distance = a.pos.distance(b.pos);
+				let previous = a.previous.distance(b.previous);
+				if previous > 1.0 {
+					self.gesture.pinch += distance / previous - 1.0;
+				}
+			},
+			_ => {}
+		}
+		let multiTouch = free.len() > 1;
+
+		let tapTime = self.tapTime;
+		let tapDistance = self.tapDistance;
+		for point in self.touches.values_mut() {
+			point.age += dt;
+			point.previous = point.pos;
+			if multiTouch || point.age > tapTime || point.pos.distance(point.start) > tapDistance {
+				point.tapCancelled = true;
+			}
+		}
+	}
+
+	pub fn touches(&self) -> impl Iterator<Item = &TouchPoint> {
+		self.touches.values()
+	}
+
+	pub fn count(&self) -> usize {
+		self.touches.len()
+	}
+
+	// Taps that finished since the last update, treat them as clicks
+	pub fn taps(&self) -> &[Vec2] {
+		&self.taps
+	}
+
+	pub fn gesture(&self) -> TouchGesture {
+		self.gesture
+	}
+
+	// Zero for unknown or released joysticks
+	pub fn joystick(&self, index: usize) -> Vec2 {
+		self.joysticks.get(index).map(|j| j.value).unwrap_or(Vec2::ZERO)
+	}
+
+	// Any touch seen, e.g. to only show joysticks on touch screens
+	pub fn wasUsed(&self) -> bool {
+		self.used
+	}
+
+	pub fn viewport(&self) -> Vec2 {
+		self.viewport
+	}
+}
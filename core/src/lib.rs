@@ -1,5 +1,13 @@
 pub mod render;
+pub mod camera;
+pub mod geometry;
+pub mod gui;
+pub mod input;
+pub mod profiler;
+pub mod storage;
+pub mod timestep;
+pub mod timing;
+pub mod trace;
 mod test_app;
-mod camera;
 
 pub use self::test_app::TestApp;
@@ -0,0 +1,141 @@
+#![allow(non_snake_case)]
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use crate::trace::{chromeTraceJson, traceTime, TraceEvent};
+
+/*
+ * CPU timing spans for finding frame hitches. Spans go into a per-thread ring buffer holding the last
+ * few seconds and can be exported as Chrome trace JSON. Use `profileScope!` for a lexical scope or
+ * `begin`/`end` for spans that don't fit one.
+ */
+
+// Spans kept before the oldest are overwritten
+const CAPACITY: usize = 20_000;
+// Trace row for CPU spans
+const CPU_TRACE_TID: u32 = 1;
+
+#[derive(Copy, Clone, Debug)]
+struct Span {
+	name: &'static str,
+	// Microseconds, trace time
+	start: f64,
+	duration: f64,
+}
+
+thread_local! {
+	static ENABLED: Cell<bool> = const { Cell::new(true) };
+	static SPANS: RefCell<VecDeque<Span>> = RefCell::new(VecDeque::with_capacity(CAPACITY));
+	static OPEN: RefCell<Vec<(&'static str, f64)>> = const { RefCell::new(Vec::new()) };
+}
+
+// Times the rest of the enclosing block
+#[macro_export]
+macro_rules! profileScope {
+	($name:expr) => {
+		let _profileScope = $crate::profiler::ProfileScope::new($name);
+	};
+}
+
+pub fn setEnabled(enabled: bool) {
+	ENABLED.with(|e| e.set(enabled));
+	if !enabled {
+		OPEN.with(|open| open.borrow_mut().clear());
+	}
+}
+
+pub fn isEnabled() -> bool {
+	ENABLED.with(|e| e.get())
+}
+
+pub fn begin(name: &'static str) {
+	if isEnabled() {
+		OPEN.with(|open| open.borrow_mut().push((name, traceTime())));
+	}
+}
+
+// Ends the innermost span from `begin`
+pub fn end() {
+	if let Some((name, start)) = OPEN.with(|open| open.borrow_mut().pop()) {
+		record(name, start);
+	}
+}
+
+fn record(name: &'static str, start: f64) {
+	let duration = traceTime() - start;
+	SPANS.with(|spans| {
+		let mut spans = spans.borrow_mut();
+		if spans.len() >= CAPACITY {
+			spans.pop_front();
+		}
+		spans.push_back(Span { name, start, duration });
+	});
+}
+
+pub struct ProfileScope {
+	name: &'static str,
+	// None while disabled
+	start: Option<f64>,
+}
+
+impl ProfileScope {
+	pub fn new(name: &'static str) -> Self {
+		ProfileScope {
+			name,
+			start: isEnabled().then(traceTime),
+		}
+	}
+}
+
+impl Drop for ProfileScope {
+	fn drop(&mut self) {
+		if let Some(start) = self.start {
+			record(self.name, start);
+		}
+	}
+}
+
+// Number of recorded spans
+pub fn len() -> usize {
+	SPANS.with(|spans| spans.borrow().len())
+}
+
+pub fn clear() {
+	SPANS.with(|spans| spans.borrow_mut().clear());
+}
+
+pub fn traceEvents() -> Vec<TraceEvent> {
+	SPANS.with(|spans| {
+		spans.borrow().iter().map(|span| TraceEvent::complete(span.name, "cpu", CPU_TRACE_TID, span.start, span.duration)).collect()
+	})
+}
+
+// The recorded spans plus `extra` events (e.g. GPU passes) as Chrome trace JSON
+pub fn chromeTrace<'a>(extra: impl IntoIterator<Item = &'a TraceEvent>) -> Result<String, String> {
+	let mut events = traceEvents();
+	events.extend(extra.into_iter().cloned());
+	chromeTraceJson(&events)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{begin, clear, end, traceEvents};
+
+	#[test]
+	fn scopesNestInTime() {
+		clear();
+		{
+			profileScope!("outer");
+			begin("inner");
+			end();
+		}
+		// Spans are recorded when they end
+		let events = traceEvents();
+		assert_eq!(events.len(), 2);
+		let (inner, outer) = (&events[0], &events[1]);
+		assert_eq!(inner.name, "inner");
+		assert_eq!(outer.name, "outer");
+		assert!(inner.ts >= outer.ts);
+		assert!(inner.ts + inner.dur <= outer.ts + outer.dur);
+	}
+}
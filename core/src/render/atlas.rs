@@ -0,0 +1,331 @@
+#![allow(non_snake_case)]
+
+use std::collections::HashMap;
+use glam::Vec4;
+
+/*
+ * Tightly packed RGBA8 pixels, first row is the top of the image
+ */
+#[derive(Clone)]
+pub struct Image {
+	pub width: u32,
+	pub height: u32,
+	pub pixels: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl Image {
+	pub fn new(width: u32, height: u32) -> Self {
+		Image {
+			width,
+			height,
+			pixels: vec![0; (width * height * 4) as usize],
+		}
+	}
+
+	pub fn fromRgba(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self, String> {
+		if pixels.len() != (width * height * 4) as usize {
+			return Err(format!("Image data size mismatch: expected {} bytes, got {}", width * height * 4, pixels.len()));
+		}
+		Ok(Image {
+			width,
+			height,
+			pixels,
+		})
+	}
+
+	// Clipped to this image, nothing is copied when `x`/`y` are past its edges
+	pub fn blit(&mut self, src: &Image, x: u32, y: u32) {
+		let len = (src.width.min(self.width.saturating_sub(x)) * 4) as usize;
+		if len == 0 {
+			return;
+		}
+		for row in 0..src.height.min(self.height.saturating_sub(y)) {
+			let srcStart = (row * src.width * 4) as usize;
+			let dstStart = (((y + row) * self.width + x) * 4) as usize;
+			self.pixels[dstStart..dstStart + len].copy_from_slice(&src.pixels[srcStart..srcStart + len]);
+		}
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AtlasRect {
+	pub x: u32,
+	pub y: u32,
+	pub width: u32,
+	pub height: u32,
+}
+
+#[allow(dead_code)]
+impl AtlasRect {
+	// (u0, v0, u1, v1) as used by `Sprite::uv`
+	pub fn uv(&self, atlasWidth: u32, atlasHeight: u32) -> Vec4 {
+		Vec4::new(
+			self.x as f32 / atlasWidth as f32,
+			self.y as f32 / atlasHeight as f32,
+			(self.x + self.width) as f32 / atlasWidth as f32,
+			(self.y + self.height) as f32 / atlasHeight as f32,
+		)
+	}
+}
+
+#[derive(Copy, Clone)]
+struct SkylineNode {
+	x: u32,
+	y: u32,
+	width: u32,
+}
+
+/*
+ * Skyline bottom-left rectangle packer, can be kept around to add rects at runtime
+ */
+pub struct RectPacker {
+	pub width: u32,
+	pub height: u32,
+	skyline: Vec<SkylineNode>,
+}
+
+#[allow(dead_code)]
+impl RectPacker {
+	pub fn new(width: u32, height: u32) -> Self {
+		RectPacker {
+			width,
+			height,
+			skyline: vec![SkylineNode { x: 0, y: 0, width }],
+		}
+	}
+
+	pub fn pack(&mut self, width: u32, height: u32) -> Option<AtlasRect> {
+		let mut best: Option<(usize, u32)> = None;
+		let mut bestTop = u32::MAX;
+		let mut bestWidth = u32::MAX;
+		for i in 0..self.skyline.len() {
+			if let Some(y) = self.fit(i, width, height) {
+				let top = y + height;
+				if top < bestTop || (top == bestTop && self.skyline[i].width < bestWidth) {
+					best = Some((i, y));
+					bestTop = top;
+					bestWidth = self.skyline[i].width;
+				}
+			}
+		}
+
+		let (index, y) = best?;
+		let rect = AtlasRect {
+			x: self.skyline[index].x,
+			y,
+			width,
+			height,
+		};
+		self.addNode(index, rect);
+		Some(rect)
+	}
+
+	fn fit(&self, index: usize, width: u32, height: u32) -> Option<u32> {
+		let x = self.skyline[index].x;
+		if x + width > self.width {
+			return None;
+		}
+		let mut widthLeft = width;
+		let mut y = 0;
+		let mut i = index;
+		loop {
+			let node = self.skyline.get(i)?;
+			y = y.max(node.y);
+			if y + height > self.height {
+				return None;
+			}
+			if node.width >= widthLeft {
+				return Some(y);
+			}
+			widthLeft -= node.width;
+			i += 1;
+		}
+	}
+
+	fn addNode(&mut self, index: usize, rect: AtlasRect) {
+		self.skyline.insert(index, SkylineNode {
+			x: rect.x,
+			y: rect.y + rect.height,
+			width: rect.width,
+		});
+
+		// Trim nodes now covered by the new one
+		let i = index + 1;
+		while i < self.skyline.len() {
+			let prevEnd = self.skyline[i - 1].x + self.skyline[i - 1].width;
+			let node = &mut self.skyline[i];
+			if node.x >= prevEnd {
+				break;
+			}
+			let shrink = prevEnd - node.x;
+			if node.width <= shrink {
+				self.skyline.remove(i);
+			} else {
+				node.x += shrink;
+				node.width -= shrink;
+				break;
+			}
+		}
+
+		// Merge neighbours at the same height
+		let mut i = 0;
+		while i + 1 < self.skyline.len() {
+			if self.skyline[i].y == self.skyline[i + 1].y {
+				self.skyline[i].width += self.skyline[i + 1].width;
+				self.skyline.remove(i + 1);
+			} else {
+				i += 1;
+			}
+		}
+	}
+}
+
+pub struct TextureAtlas {
+	pub width: u32,
+	pub height: u32,
+	// Region names in the order they were added to the builder
+	pub names: Vec<String>,
+	pub regions: HashMap<String, AtlasRect>,
+}
+
+#[allow(dead_code)]
+impl TextureAtlas {
+	pub fn getRect(&self, name: &str) -> Option<AtlasRect> {
+		self.regions.get(name).copied()
+	}
+
+	pub fn getUv(&self, name: &str) -> Option<Vec4> {
+		self.regions.get(name).map(|r| r.uv(self.width, self.height))
+	}
+}
+
+pub struct AtlasBuilder {
+	images: Vec<(String, Image)>,
+	pub padding: u32,
+	pub maxSize: u32,
+}
+
+impl Default for AtlasBuilder {
+	fn default() -> AtlasBuilder {
+		AtlasBuilder {
+			images: Vec::new(),
+			padding: 1,
+			maxSize: 4096,
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl AtlasBuilder {
+	pub fn add(&mut self, name: &str, image: Image) -> Result<(), String> {
+		if self.images.iter().any(|(n, _)| n == name) {
+			return Err(format!("Atlas already has an image named `{}`", name));
+		}
+		self.images.push((name.to_string(), image));
+		Ok(())
+	}
+
+	// Packs tallest first and doubles the atlas size until everything fits
+	pub fn build(&self) -> Result<(Image, TextureAtlas), String> {
+		let mut order: Vec<usize> = (0..self.images.len()).collect();
+		order.sort_by(|a, b| {
+			let (a, b) = (&self.images[*a].1, &self.images[*b].1);
+			b.height.cmp(&a.height).then(b.width.cmp(&a.width))
+		});
+
+		let mut size = 64;
+		while size <= self.maxSize {
+			if let Some(regions) = self.tryPack(&order, size) {
+				let mut image = Image::new(size, size);
+				for (name, src) in &self.images {
+					let rect = regions[name];
+					image.blit(src, rect.x, rect.y);
+				}
+				let atlas = TextureAtlas {
+					width: size,
+					height: size,
+					names: self.images.iter().map(|(name, _)| name.clone()).collect(),
+					regions,
+				};
+				return Ok((image, atlas));
+			}
+			size *= 2;
+		}
+		Err(format!("Failed to pack {} images into a {}x{} atlas", self.images.len(), self.maxSize, self.maxSize))
+	}
+
+	fn tryPack(&self, order: &[usize], size: u32) -> Option<HashMap<String, AtlasRect>> {
+		let mut packer = RectPacker::new(size, size);
+		let mut regions = HashMap::with_capacity(self.images.len());
+		for i in order {
+			let (name, image) = &self.images[*i];
+			let rect = packer.pack(image.width + self.padding, image.height + self.padding)?;
+			regions.insert(name.clone(), AtlasRect {
+				width: image.width,
+				height: image.height,
+				..rect
+			});
+		}
+		Some(regions)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::render::{AtlasBuilder, AtlasRect, Image, RectPacker};
+
+	fn overlaps(a: &AtlasRect, b: &AtlasRect) -> bool {
+		a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+	}
+
+	#[test]
+	fn blitClipsToTheImage() {
+		let mut dst = Image::new(4, 4);
+		let src = Image::fromRgba(2, 2, vec![255; 16]).unwrap();
+		dst.blit(&src, 3, 3);
+		let lit = dst.pixels.chunks(4).filter(|p| p[0] == 255).count();
+		assert_eq!(lit, 1);
+		assert_eq!(dst.pixels[(3 * 4 + 3) * 4], 255);
+		// Past the right and bottom edges
+		dst.blit(&src, 5, 0);
+		dst.blit(&src, 0, 5);
+		assert_eq!(dst.pixels.chunks(4).filter(|p| p[0] == 255).count(), 1);
+	}
+
+	#[test]
+	fn packsWithoutOverlap() {
+		let mut packer = RectPacker::new(64, 64);
+		let sizes = [(30, 20), (20, 30), (10, 10), (34, 8), (16, 16), (40, 12), (8, 24)];
+		let rects: Vec<AtlasRect> = sizes.iter().map(|(w, h)| packer.pack(*w, *h).unwrap()).collect();
+		for (i, a) in rects.iter().enumerate() {
+			assert_eq!((a.width, a.height), sizes[i]);
+			assert!(a.x + a.width <= 64 && a.y + a.height <= 64);
+			for b in &rects[i + 1..] {
+				assert!(!overlaps(a, b), "{:?} overlaps {:?}", a, b);
+			}
+		}
+	}
+
+	#[test]
+	fn fillsBottomLeftFirst() {
+		let mut packer = RectPacker::new(32, 32);
+		assert_eq!(packer.pack(16, 8), Some(AtlasRect { x: 0, y: 0, width: 16, height: 8 }));
+		assert_eq!(packer.pack(16, 4), Some(AtlasRect { x: 16, y: 0, width: 16, height: 4 }));
+		// Lowest top edge wins, on top of the shorter rect
+		assert_eq!(packer.pack(16, 4), Some(AtlasRect { x: 16, y: 4, width: 16, height: 4 }));
+		// Too tall for what's left, then too wide for the packer
+		assert_eq!(packer.pack(8, 25), None);
+		assert_eq!(packer.pack(33, 1), None);
+	}
+
+	#[test]
+	fn rejectsDuplicateNames() {
+		let mut builder = AtlasBuilder::default();
+		builder.add("a", Image::new(4, 4)).unwrap();
+		assert!(builder.add("a", Image::new(2, 2)).is_err());
+		let (_, atlas) = builder.build().unwrap();
+		assert_eq!(atlas.names, ["a"]);
+		assert_eq!(atlas.getRect("a").map(|r| (r.width, r.height)), Some((4, 4)));
+	}
+}
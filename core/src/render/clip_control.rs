@@ -0,0 +1,35 @@
+#![allow(non_snake_case)]
+
+use std::ffi::{c_void, CStr};
+use glow::{Context, HasContext, LOWER_LEFT, ZERO_TO_ONE};
+use log::{info, warn};
+use crate::camera::ClipDepth;
+
+/*
+ * Switches clip space depth to 0..1, which reverse-Z needs to be any better than standard depth.
+ * glow doesn't wrap glClipControl, `getProcAddress` is the loader the context was created with.
+ * Runs once on the raw context before any `RenderContext` exists, so it isn't part of that trait.
+ * WebGL2 has no core clip control, the web runner stays on -1..1.
+ */
+pub fn enableClipControl(gl: &Context, getProcAddress: impl Fn(&CStr) -> *const c_void) -> ClipDepth {
+	let version = gl.version();
+	let extensions = gl.supported_extensions();
+	let name = if !version.is_embedded && ((version.major, version.minor) >= (4, 5) || extensions.contains("GL_ARB_clip_control")) {
+		c"glClipControl"
+	} else if version.is_embedded && extensions.contains("GL_EXT_clip_control") {
+		c"glClipControlEXT"
+	} else {
+		info!("Clip control unavailable, depth stays -1..1 without reverse-Z");
+		return ClipDepth::NegativeOneToOne;
+	};
+	let address = getProcAddress(name);
+	if address.is_null() {
+		warn!("Failed to load {:?}", name);
+		return ClipDepth::NegativeOneToOne;
+	}
+	unsafe {
+		let clipControl: unsafe extern "system" fn(u32, u32) = std::mem::transmute(address);
+		clipControl(LOWER_LEFT, ZERO_TO_ONE);
+	}
+	ClipDepth::ZeroToOne
+}
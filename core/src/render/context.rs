@@ -0,0 +1,110 @@
+#![allow(non_snake_case)]
+
+use glam::{Mat4, Vec2, Vec3, Vec4};
+use crate::render::TextureFilter;
+
+/*
+ * The GL calls the renderers make, so they can run against something other than a live context.
+ * `GlowContext` forwards to glow, `RecordingContext` keeps the commands in memory for tests.
+ * Enums (targets, modes, capabilities) are the raw GL values, objects are ids owned by the context.
+ */
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ProgramId(pub u32);
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BufferId(pub u32);
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct VertexArrayId(pub u32);
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TextureId(pub u32);
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct QueryId(pub u32);
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ShaderStage {
+	Vertex,
+	Geometry,
+	Fragment,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Uniform {
+	Int(i32),
+	UInt(u32),
+	Float(f32),
+	Vec2(Vec2),
+	Vec3(Vec3),
+	Vec4(Vec4),
+	Mat4(Mat4),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RenderObject {
+	Program(ProgramId),
+	Buffer(BufferId),
+	VertexArray(VertexArrayId),
+	Texture(TextureId),
+}
+
+pub trait RenderContext {
+	// Compiles and links the stages into one program
+	fn createProgram(&self, stages: &[(ShaderStage, &str)]) -> Result<ProgramId, String>;
+	fn deleteProgram(&self, program: ProgramId);
+	fn useProgram(&self, program: Option<ProgramId>);
+	fn attribLocation(&self, program: ProgramId, name: &str) -> Option<u32>;
+	// Like GL, applies to the program in use, `program` is only for the location lookup
+	fn setUniform(&self, program: ProgramId, name: &str, value: Uniform);
+
+	fn createVertexArray(&self) -> Result<VertexArrayId, String>;
+	fn deleteVertexArray(&self, vao: VertexArrayId);
+	fn bindVertexArray(&self, vao: Option<VertexArrayId>);
+	// Float attribute, `stride` and `offset` in bytes
+	fn vertexAttribPointer(&self, location: u32, size: i32, stride: i32, offset: i32);
+	fn enableVertexAttribArray(&self, location: u32);
+
+	fn createBuffer(&self) -> Result<BufferId, String>;
+	fn deleteBuffer(&self, buffer: BufferId);
+	fn bindBuffer(&self, target: u32, buffer: Option<BufferId>);
+	fn bufferData(&self, target: u32, data: &[u8], usage: u32);
+
+	// RGBA8, clamped to edge
+	fn createTexture(&self, width: u32, height: u32, pixels: &[u8], filter: TextureFilter) -> Result<TextureId, String>;
+	fn updateTexture(&self, texture: TextureId, x: u32, y: u32, width: u32, height: u32, pixels: &[u8]);
+	fn deleteTexture(&self, texture: TextureId);
+	fn bindTexture(&self, unit: u32, texture: Option<TextureId>);
+
+	fn drawArrays(&self, mode: u32, first: i32, count: i32);
+	// u32 indices, `offset` in bytes
+	fn drawElements(&self, mode: u32, count: i32, offset: i32);
+
+	fn viewport(&self, x: i32, y: i32, width: i32, height: i32);
+	fn clearColor(&self, r: f32, g: f32, b: f32, a: f32);
+	fn clearDepth(&self, depth: f32);
+	// `mask` of COLOR_BUFFER_BIT, DEPTH_BUFFER_BIT, ...
+	fn clear(&self, mask: u32);
+	fn enable(&self, capability: u32);
+	fn disable(&self, capability: u32);
+	fn depthFunc(&self, func: u32);
+	fn blendFunc(&self, src: u32, dst: u32);
+	fn scissor(&self, x: i32, y: i32, width: i32, height: i32);
+
+	// TIME_ELAPSED queries, the rest of the query calls are only used when this is true
+	fn supportsTimerQueries(&self) -> bool;
+	fn createQuery(&self) -> Result<QueryId, String>;
+	fn deleteQuery(&self, query: QueryId);
+	fn beginQuery(&self, target: u32, query: QueryId);
+	fn endQuery(&self, target: u32);
+	// None until the GPU has the result
+	fn queryResult(&self, query: QueryId) -> Option<u64>;
+	// Results in flight are meaningless after a disjoint event (GPU reset, throttling), web only
+	fn queriesDisjoint(&self) -> bool {
+		false
+	}
+
+	// Name shown in GL debug messages and capture tools
+	fn setLabel(&self, _object: RenderObject, _label: &str) {}
+}
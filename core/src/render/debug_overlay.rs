@@ -0,0 +1,137 @@
+#![allow(non_snake_case)]
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::rc::Rc;
+use std::sync::Mutex;
+use glam::{vec3, Mat4, Vec4};
+use log::Record;
+use crate::profileScope;
+use crate::render::debug_font::{debugFontImage, debugGlyphIndex, DEBUG_FONT_COLUMNS, DEBUG_FONT_HEIGHT, DEBUG_FONT_WIDTH};
+use crate::render::{RenderContext, SpriteBatch, Texture, TextureFilter};
+
+const LOG_LINES: usize = 8;
+
+// Filled by the runners' loggers, see `recordDebugLog`
+static LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+pub fn recordDebugLog(record: &Record) {
+	if let Ok(mut log) = LOG.lock() {
+		if log.len() == LOG_LINES {
+			log.pop_front();
+		}
+		log.push_back(format!("[{}] {}", record.level(), record.args()));
+	}
+}
+
+struct DebugText {
+	x: f32,
+	y: f32,
+	text: String,
+	color: Vec4,
+}
+
+/*
+ * Screen space text using the embedded bitmap font, origin top-left, y down, in pixels.
+ * Text is queued during the frame and drawn by `drawFlush`.
+ */
+pub struct DebugOverlay {
+	batch: SpriteBatch,
+	texture: Texture,
+	texts: Vec<DebugText>,
+	pub scale: f32,
+	pub showLog: bool,
+	pub enabled: bool,
+}
+
+#[macro_export]
+macro_rules! debugText {
+	($overlay:expr, $x:expr, $y:expr, $($arg:tt)*) => {
+		$overlay.debugText($x, $y, format_args!($($arg)*))
+	};
+}
+
+#[allow(dead_code)]
+impl DebugOverlay {
+	pub fn new(context: Rc<dyn RenderContext>) -> Result<Self, String> {
+		let image = debugFontImage();
+		let texture = Texture::fromRgba(context.clone(), image.width, image.height, &image.pixels, TextureFilter::Nearest)?;
+		Ok(DebugOverlay {
+			batch: SpriteBatch::new(context, 1024)?,
+			texture,
+			texts: Vec::new(),
+			scale: 2.0,
+			showLog: true,
+			enabled: true,
+		})
+	}
+
+	pub fn debugText(&mut self, x: f32, y: f32, text: impl Display) {
+		self.debugTextColor(x, y, Vec4::ONE, text);
+	}
+
+	pub fn debugTextColor(&mut self, x: f32, y: f32, color: Vec4, text: impl Display) {
+		if !self.enabled {
+			return;
+		}
+		self.texts.push(DebugText {
+			x,
+			y,
+			text: text.to_string(),
+			color,
+		});
+	}
+
+	pub fn lineHeight(&self) -> f32 {
+		DEBUG_FONT_HEIGHT as f32 * self.scale
+	}
+
+	pub fn drawFlush(&mut self, width: f32, height: f32) {
+		profileScope!("DebugOverlay::drawFlush");
+		if self.showLog && self.enabled {
+			let lines: Vec<String> = LOG.lock().map(|log| log.iter().cloned().collect()).unwrap_or_default();
+			let top = height - (lines.len() as f32 + 0.5) * self.lineHeight();
+			for (i, line) in lines.into_iter().enumerate() {
+				self.debugTextColor(4.0, top + i as f32 * self.lineHeight(), Vec4::new(0.8, 0.8, 0.8, 1.0), line);
+			}
+		}
+
+		let (glyphWidth, glyphHeight) = (DEBUG_FONT_WIDTH as f32 * self.scale, DEBUG_FONT_HEIGHT as f32 * self.scale);
+		let (texWidth, texHeight) = (self.texture.width as f32, self.texture.height as f32);
+		let shadow = Vec4::new(0.0, 0.0, 0.0, 0.75);
+		for text in self.texts.drain(..) {
+			let (mut x, mut y) = (text.x, text.y);
+			for ch in text.text.chars() {
+				if ch == '\n' {
+					x = text.x;
+					y += glyphHeight;
+					continue;
+				}
+				let index = debugGlyphIndex(ch) as u32;
+				let u = (index % DEBUG_FONT_COLUMNS * DEBUG_FONT_WIDTH) as f32 / texWidth;
+				let v = (index / DEBUG_FONT_COLUMNS * DEBUG_FONT_HEIGHT) as f32 / texHeight;
+				let uv = Vec4::new(u, v, u + DEBUG_FONT_WIDTH as f32 / texWidth, v + DEBUG_FONT_HEIGHT as f32 / texHeight);
+
+				// y down, so the "bottom" corners have the larger y
+				for (offset, color, layer) in [(self.scale, shadow, 0.0), (0.0, text.color, 1.0)] {
+					let (qx, qy) = (x + offset, y + offset);
+					self.batch.pushQuad(&self.texture, [
+						vec3(qx, qy + glyphHeight, 0.0),
+						vec3(qx + glyphWidth, qy + glyphHeight, 0.0),
+						vec3(qx + glyphWidth, qy, 0.0),
+						vec3(qx, qy, 0.0),
+					], uv, color, layer);
+				}
+				x += glyphWidth;
+			}
+		}
+
+		let projection = Mat4::orthographic_rh_gl(0.0, width, height, 0.0, -1.0, 1.0);
+		self.batch.drawFlush(&projection);
+	}
+
+	pub fn destroy(&mut self) {
+		self.batch.destroy();
+		self.texture.destroy();
+	}
+}
@@ -0,0 +1,115 @@
+#![allow(non_snake_case)]
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use glam::{vec2, Vec2, Vec4};
+use log::warn;
+use crate::render::{AtlasRect, RectPacker, RenderContext, Texture, TextureFilter};
+
+#[derive(Copy, Clone)]
+pub struct Glyph {
+	// None for glyphs without pixels (space, tab)
+	pub rect: Option<AtlasRect>,
+	// Bitmap bottom-left relative to the pen position on the baseline, y up
+	pub offset: Vec2,
+	pub size: Vec2,
+	pub advance: f32,
+}
+
+/*
+ * TTF/OTF font rasterized at a fixed pixel size, glyphs are added to the atlas on first use
+ */
+pub struct Font {
+	font: fontdue::Font,
+	pub size: f32,
+	pub ascent: f32,
+	pub descent: f32,
+	pub lineHeight: f32,
+	glyphs: HashMap<char, Glyph>,
+	packer: RectPacker,
+	pub texture: Texture,
+}
+
+const ATLAS_SIZE: u32 = 1024;
+const GLYPH_PADDING: u32 = 1;
+
+#[allow(dead_code)]
+impl Font {
+	pub fn fromBytes(context: Rc<dyn RenderContext>, bytes: &[u8], size: f32) -> Result<Self, String> {
+		let font = fontdue::Font::from_bytes(bytes, fontdue::FontSettings {
+			scale: size,
+			..fontdue::FontSettings::default()
+		}).map_err(|e| format!("Failed to load font: {}", e))?;
+
+		let (ascent, descent, lineHeight) = match font.horizontal_line_metrics(size) {
+			Some(metrics) => (metrics.ascent, metrics.descent, metrics.new_line_size),
+			None => (size, 0.0, size),
+		};
+
+		let pixels = vec![0; (ATLAS_SIZE * ATLAS_SIZE * 4) as usize];
+		let texture = Texture::fromRgba(context, ATLAS_SIZE, ATLAS_SIZE, &pixels, TextureFilter::Linear)?;
+
+		Ok(Font {
+			font,
+			size,
+			ascent,
+			descent,
+			lineHeight,
+			glyphs: HashMap::new(),
+			packer: RectPacker::new(ATLAS_SIZE, ATLAS_SIZE),
+			texture,
+		})
+	}
+
+	pub fn glyph(&mut self, ch: char) -> Glyph {
+		if let Some(glyph) = self.glyphs.get(&ch) {
+			return *glyph;
+		}
+
+		let (metrics, coverage) = self.font.rasterize(ch, self.size);
+		let (width, height) = (metrics.width as u32, metrics.height as u32);
+		let mut rect = None;
+		if width > 0 && height > 0 {
+			match self.packer.pack(width + GLYPH_PADDING, height + GLYPH_PADDING) {
+				Some(packed) => {
+					// White with coverage as alpha so it can be tinted by the sprite shader
+					let mut pixels = Vec::with_capacity(coverage.len() * 4);
+					for alpha in coverage {
+						pixels.extend_from_slice(&[255, 255, 255, alpha]);
+					}
+					self.texture.update(packed.x, packed.y, width, height, &pixels);
+					rect = Some(AtlasRect {
+						width,
+						height,
+						..packed
+					});
+				}
+				None => warn!("Font atlas is full, skipping glyph '{}'", ch),
+			}
+		}
+
+		let glyph = Glyph {
+			rect,
+			offset: vec2(metrics.xmin as f32, metrics.ymin as f32),
+			size: vec2(width as f32, height as f32),
+			advance: metrics.advance_width,
+		};
+		self.glyphs.insert(ch, glyph);
+		glyph
+	}
+
+	pub fn kern(&self, left: char, right: char) -> f32 {
+		self.font.horizontal_kern(left, right, self.size).unwrap_or(0.0)
+	}
+
+	pub fn glyphUv(&self, glyph: &Glyph) -> Vec4 {
+		match glyph.rect {
+			Some(rect) => rect.uv(self.texture.width, self.texture.height),
+			None => Vec4::ZERO,
+		}
+	}
+
+	pub fn destroy(&mut self) {
+		self.texture.destroy();
+	}
+}
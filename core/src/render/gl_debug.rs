@@ -0,0 +1,149 @@
+#![allow(non_snake_case)]
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use glow::*;
+use log::{error, info, log, Level};
+
+/*
+ * Opt-in GL debug layer. With KHR_debug (native) driver messages are forwarded to `log` as they
+ * happen, without it (WebGL2, macOS) `checkGlError` polls `get_error` after every `GlowContext` call in
+ * debug builds.
+ * Objects are labelled so they show up by name in RenderDoc and similar tools.
+ */
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+// The KHR_debug callback is installed, `get_error` checks are redundant
+static OUTPUT: AtomicBool = AtomicBool::new(false);
+
+// Errors reported per check before giving up, `get_error` can keep returning on a lost context
+const MAX_ERRORS: usize = 8;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum GlDebugSeverity {
+	Notification,
+	Low,
+	Medium,
+	High,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum GlObject {
+	Program(Program),
+	Buffer(Buffer),
+	VertexArray(VertexArray),
+	Texture(Texture),
+}
+
+// glow only takes the callback through `&mut`, call this before the context is put in an `Rc`
+pub fn enableGlDebug(gl: &mut Context, minSeverity: GlDebugSeverity) {
+	ENABLED.store(true, Ordering::Relaxed);
+	if !gl.supports_debug() {
+		if cfg!(debug_assertions) {
+			info!("KHR_debug unavailable, checking GL errors after calls");
+		} else {
+			info!("KHR_debug unavailable, GL error checks only run in debug builds");
+		}
+		return;
+	}
+	unsafe {
+		gl.enable(DEBUG_OUTPUT);
+		// Report from inside the failing call so the backtrace points at it
+		gl.enable(DEBUG_OUTPUT_SYNCHRONOUS);
+		let severities = [
+			(GlDebugSeverity::Notification, DEBUG_SEVERITY_NOTIFICATION),
+			(GlDebugSeverity::Low, DEBUG_SEVERITY_LOW),
+			(GlDebugSeverity::Medium, DEBUG_SEVERITY_MEDIUM),
+			(GlDebugSeverity::High, DEBUG_SEVERITY_HIGH),
+		];
+		for (severity, glSeverity) in severities {
+			gl.debug_message_control(DONT_CARE, DONT_CARE, glSeverity, &[], severity >= minSeverity);
+		}
+		gl.debug_message_callback(|source, msgType, id, severity, message| {
+			let level = match severity {
+				DEBUG_SEVERITY_HIGH => Level::Error,
+				DEBUG_SEVERITY_MEDIUM => Level::Warn,
+				DEBUG_SEVERITY_LOW => Level::Info,
+				_ => Level::Debug,
+			};
+			log!(level, "GL {} {} ({}): {}", sourceName(source), typeName(msgType), id, message);
+		});
+	}
+	OUTPUT.store(true, Ordering::Relaxed);
+	info!("GL debug output enabled");
+}
+
+pub fn isGlDebugEnabled() -> bool {
+	ENABLED.load(Ordering::Relaxed)
+}
+
+// Logs any pending GL errors, returns true if there were some. Only polls in debug builds without KHR_debug
+pub fn checkGlError(gl: &Context, context: &str) -> bool {
+	if !cfg!(debug_assertions) || !ENABLED.load(Ordering::Relaxed) || OUTPUT.load(Ordering::Relaxed) {
+		return false;
+	}
+	let mut failed = false;
+	for _ in 0..MAX_ERRORS {
+		let code = unsafe { gl.get_error() };
+		if code == NO_ERROR {
+			break;
+		}
+		error!("GL error {} in {}", errorName(code), context);
+		failed = true;
+	}
+	failed
+}
+
+// Only takes effect with KHR_debug, WebGL has no object labels
+#[cfg(not(target_arch = "wasm32"))]
+pub fn labelObject(gl: &Context, object: GlObject, label: &str) {
+	if !OUTPUT.load(Ordering::Relaxed) {
+		return;
+	}
+	let (identifier, name) = match object {
+		GlObject::Program(program) => (PROGRAM, program.0.get()),
+		GlObject::Buffer(buffer) => (BUFFER, buffer.0.get()),
+		GlObject::VertexArray(vao) => (VERTEX_ARRAY, vao.0.get()),
+		GlObject::Texture(texture) => (TEXTURE, texture.0.get()),
+	};
+	unsafe {
+		gl.object_label(identifier, name, Some(label));
+	}
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn labelObject(_gl: &Context, _object: GlObject, _label: &str) {}
+
+fn errorName(code: u32) -> String {
+	match code {
+		INVALID_ENUM => "INVALID_ENUM".to_string(),
+		INVALID_VALUE => "INVALID_VALUE".to_string(),
+		INVALID_OPERATION => "INVALID_OPERATION".to_string(),
+		INVALID_FRAMEBUFFER_OPERATION => "INVALID_FRAMEBUFFER_OPERATION".to_string(),
+		OUT_OF_MEMORY => "OUT_OF_MEMORY".to_string(),
+		CONTEXT_LOST => "CONTEXT_LOST".to_string(),
+		_ => format!("0x{:04X}", code),
+	}
+}
+
+fn sourceName(source: u32) -> &'static str {
+	match source {
+		DEBUG_SOURCE_API => "api",
+		DEBUG_SOURCE_WINDOW_SYSTEM => "window system",
+		DEBUG_SOURCE_SHADER_COMPILER => "shader compiler",
+		DEBUG_SOURCE_THIRD_PARTY => "third party",
+		DEBUG_SOURCE_APPLICATION => "application",
+		_ => "other",
+	}
+}
+
+fn typeName(msgType: u32) -> &'static str {
+	match msgType {
+		DEBUG_TYPE_ERROR => "error",
+		DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated",
+		DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+		DEBUG_TYPE_PORTABILITY => "portability",
+		DEBUG_TYPE_PERFORMANCE => "performance",
+		DEBUG_TYPE_MARKER => "marker",
+		_ => "other",
+	}
+}
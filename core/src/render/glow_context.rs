@@ -0,0 +1,534 @@
+#![allow(non_snake_case)]
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use glow::*;
+use log::warn;
+use crate::render::{checkGlError, labelObject, BufferId, GlObject, ProgramId, QueryId, RenderContext, RenderObject, ShaderStage, TextureFilter, TextureId, Uniform, VertexArrayId};
+
+// glow objects by id, ids are never reused so a stale one can't hit a newer object
+struct Objects<T> {
+	objects: RefCell<HashMap<u32, T>>,
+}
+
+impl<T: Copy> Objects<T> {
+	fn new() -> Self {
+		Objects {
+			objects: RefCell::new(HashMap::new()),
+		}
+	}
+
+	fn insert(&self, id: u32, object: T) {
+		self.objects.borrow_mut().insert(id, object);
+	}
+
+	fn get(&self, id: u32) -> Option<T> {
+		self.objects.borrow().get(&id).copied()
+	}
+
+	fn remove(&self, id: u32) -> Option<T> {
+		self.objects.borrow_mut().remove(&id)
+	}
+}
+
+/*
+ * `RenderContext` on a live glow context
+ */
+pub struct GlowContext {
+	gl: Rc<Context>,
+	nextId: Cell<u32>,
+	programs: Objects<Program>,
+	buffers: Objects<Buffer>,
+	vertexArrays: Objects<VertexArray>,
+	textures: Objects<Texture>,
+	queries: Objects<Query>,
+	timerQueries: bool,
+	// Problems already logged, a bad uniform name would otherwise warn every frame
+	warned: RefCell<HashSet<String>>,
+}
+
+#[allow(dead_code)]
+impl GlowContext {
+	pub fn new(gl: Rc<Context>) -> Self {
+		let timerQueries = supportsTimerQueries(&gl);
+		GlowContext {
+			gl,
+			nextId: Cell::new(1),
+			programs: Objects::new(),
+			buffers: Objects::new(),
+			vertexArrays: Objects::new(),
+			textures: Objects::new(),
+			queries: Objects::new(),
+			timerQueries,
+			warned: RefCell::new(HashSet::new()),
+		}
+	}
+
+	pub fn gl(&self) -> &Rc<Context> {
+		&self.gl
+	}
+
+	fn nextId(&self) -> u32 {
+		let id = self.nextId.get();
+		self.nextId.set(id + 1);
+		id
+	}
+
+	// Only polls with GL debug enabled in debug builds and without KHR_debug, which reports inside the call
+	fn check(&self, call: &str) {
+		checkGlError(&self.gl, call);
+	}
+
+	fn warnOnce(&self, message: String) {
+		if self.warned.borrow_mut().insert(message.clone()) {
+			warn!("{}", message);
+		}
+	}
+
+	// None for ids that were deleted or never created here
+	fn resolve<T: Copy>(&self, objects: &Objects<T>, id: u32, kind: &str) -> Option<T> {
+		let object = objects.get(id);
+		if object.is_none() {
+			self.warnOnce(format!("Unknown {} id {}", kind, id));
+		}
+		object
+	}
+
+	// Outer None if the id is unknown and the call should be skipped
+	fn resolveBinding<T: Copy>(&self, objects: &Objects<T>, id: Option<u32>, kind: &str) -> Option<Option<T>> {
+		match id {
+			Some(id) => self.resolve(objects, id, kind).map(Some),
+			None => Some(None),
+		}
+	}
+}
+
+impl RenderContext for GlowContext {
+	fn createProgram(&self, stages: &[(ShaderStage, &str)]) -> Result<ProgramId, String> {
+		let gl = &self.gl;
+		unsafe {
+			let program = gl.create_program().map_err(|e| format!("Failed to create program: {}", e))?;
+
+			let mut shaders = Vec::with_capacity(stages.len());
+			for (stage, source) in stages {
+				let shaderType = match stage {
+					ShaderStage::Vertex => VERTEX_SHADER,
+					ShaderStage::Geometry => GEOMETRY_SHADER,
+					ShaderStage::Fragment => FRAGMENT_SHADER,
+				};
+				match compileShader(gl, source, shaderType) {
+					Ok(shader) => shaders.push(shader),
+					Err(e) => {
+						for shader in shaders {
+							gl.delete_shader(shader);
+						}
+						gl.delete_program(program);
+						return Err(e);
+					}
+				}
+			}
+			for shader in &shaders {
+				gl.attach_shader(program, *shader);
+			}
+
+			// #[cfg(not(target_arch = "wasm32"))]
+			// gl.bind_frag_data_location(program, glow::COLOR_ATTACHMENT0, "o_color");
+			gl.link_program(program);
+
+			for shader in shaders {
+				gl.delete_shader(shader);
+			}
+			if !gl.get_program_link_status(program) {
+				let error = gl.get_program_info_log(program);
+				gl.delete_program(program);
+				return Err(format!("Failed to link program: {}", error));
+			}
+
+			self.check("glLinkProgram");
+
+			let id = self.nextId();
+			self.programs.insert(id, program);
+			Ok(ProgramId(id))
+		}
+	}
+
+	fn deleteProgram(&self, program: ProgramId) {
+		if let Some(program) = self.programs.remove(program.0) {
+			unsafe {
+				self.gl.delete_program(program);
+			}
+			self.check("glDeleteProgram");
+		}
+	}
+
+	fn useProgram(&self, program: Option<ProgramId>) {
+		let Some(program) = self.resolveBinding(&self.programs, program.map(|p| p.0), "program") else {
+			return;
+		};
+		unsafe {
+			self.gl.use_program(program);
+		}
+		self.check("glUseProgram");
+	}
+
+	fn attribLocation(&self, program: ProgramId, name: &str) -> Option<u32> {
+		let program = self.resolve(&self.programs, program.0, "program")?;
+		let location = unsafe { self.gl.get_attrib_location(program, name) };
+		self.check("glGetAttribLocation");
+		location
+	}
+
+	fn setUniform(&self, program: ProgramId, name: &str, value: Uniform) {
+		let Some(glProgram) = self.resolve(&self.programs, program.0, "program") else {
+			return;
+		};
+		// Unused uniforms are optimized out by the driver
+		let Some(location) = (unsafe { self.gl.get_uniform_location(glProgram, name) }) else {
+			self.warnOnce(format!("Uniform `{}` not found in program {}", name, program.0));
+			return;
+		};
+		unsafe {
+			let loc = Some(&location);
+			match value {
+				Uniform::Int(v) => self.gl.uniform_1_i32(loc, v),
+				Uniform::UInt(v) => self.gl.uniform_1_u32(loc, v),
+				Uniform::Float(v) => self.gl.uniform_1_f32(loc, v),
+				Uniform::Vec2(v) => self.gl.uniform_2_f32(loc, v.x, v.y),
+				Uniform::Vec3(v) => self.gl.uniform_3_f32(loc, v.x, v.y, v.z),
+				Uniform::Vec4(v) => self.gl.uniform_4_f32(loc, v.x, v.y, v.z, v.w),
+				Uniform::Mat4(m) => self.gl.uniform_matrix_4_f32_slice(loc, false, &m.to_cols_array()),
+			}
+		}
+		self.check("glUniform");
+	}
+
+	fn createVertexArray(&self) -> Result<VertexArrayId, String> {
+		let vao = unsafe { self.gl.create_vertex_array() }.map_err(|e| format!("Failed to create vertex array: {}", e))?;
+		self.check("glGenVertexArrays");
+		let id = self.nextId();
+		self.vertexArrays.insert(id, vao);
+		Ok(VertexArrayId(id))
+	}
+
+	fn deleteVertexArray(&self, vao: VertexArrayId) {
+		if let Some(vao) = self.vertexArrays.remove(vao.0) {
+			unsafe {
+				self.gl.delete_vertex_array(vao);
+			}
+			self.check("glDeleteVertexArrays");
+		}
+	}
+
+	fn bindVertexArray(&self, vao: Option<VertexArrayId>) {
+		let Some(vao) = self.resolveBinding(&self.vertexArrays, vao.map(|v| v.0), "vertex array") else {
+			return;
+		};
+		unsafe {
+			self.gl.bind_vertex_array(vao);
+		}
+		self.check("glBindVertexArray");
+	}
+
+	fn vertexAttribPointer(&self, location: u32, size: i32, stride: i32, offset: i32) {
+		unsafe {
+			self.gl.vertex_attrib_pointer_f32(location, size, FLOAT, false, stride, offset);
+		}
+		self.check("glVertexAttribPointer");
+	}
+
+	fn enableVertexAttribArray(&self, location: u32) {
+		unsafe {
+			self.gl.enable_vertex_attrib_array(location);
+		}
+		self.check("glEnableVertexAttribArray");
+	}
+
+	fn createBuffer(&self) -> Result<BufferId, String> {
+		let buffer = unsafe { self.gl.create_buffer() }.map_err(|e| format!("Failed to create buffer object: {}", e))?;
+		self.check("glGenBuffers");
+		let id = self.nextId();
+		self.buffers.insert(id, buffer);
+		Ok(BufferId(id))
+	}
+
+	fn deleteBuffer(&self, buffer: BufferId) {
+		if let Some(buffer) = self.buffers.remove(buffer.0) {
+			unsafe {
+				self.gl.delete_buffer(buffer);
+			}
+			self.check("glDeleteBuffers");
+		}
+	}
+
+	fn bindBuffer(&self, target: u32, buffer: Option<BufferId>) {
+		let Some(buffer) = self.resolveBinding(&self.buffers, buffer.map(|b| b.0), "buffer") else {
+			return;
+		};
+		unsafe {
+			self.gl.bind_buffer(target, buffer);
+		}
+		self.check("glBindBuffer");
+	}
+
+	fn bufferData(&self, target: u32, data: &[u8], usage: u32) {
+		unsafe {
+			self.gl.buffer_data_u8_slice(target, data, usage);
+		}
+		self.check("glBufferData");
+	}
+
+	fn createTexture(&self, width: u32, height: u32, pixels: &[u8], filter: TextureFilter) -> Result<TextureId, String> {
+		let gl = &self.gl;
+		unsafe {
+			let texture = gl.create_texture().map_err(|e| format!("Failed to create texture: {}", e))?;
+			gl.bind_texture(TEXTURE_2D, Some(texture));
+
+			let filter = match filter {
+				TextureFilter::Nearest => NEAREST,
+				TextureFilter::Linear => LINEAR,
+			} as i32;
+			gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_MIN_FILTER, filter);
+			gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_MAG_FILTER, filter);
+			gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_WRAP_S, CLAMP_TO_EDGE as i32);
+			gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_WRAP_T, CLAMP_TO_EDGE as i32);
+
+			gl.tex_image_2d(TEXTURE_2D, 0, RGBA8 as i32, width as i32, height as i32, 0, RGBA, UNSIGNED_BYTE, PixelUnpackData::Slice(Some(pixels)));
+			gl.bind_texture(TEXTURE_2D, None);
+			self.check("glTexImage2D");
+
+			let id = self.nextId();
+			self.textures.insert(id, texture);
+			Ok(TextureId(id))
+		}
+	}
+
+	fn updateTexture(&self, texture: TextureId, x: u32, y: u32, width: u32, height: u32, pixels: &[u8]) {
+		let Some(texture) = self.resolve(&self.textures, texture.0, "texture") else {
+			return;
+		};
+		unsafe {
+			self.gl.bind_texture(TEXTURE_2D, Some(texture));
+			self.gl.tex_sub_image_2d(TEXTURE_2D, 0, x as i32, y as i32, width as i32, height as i32, RGBA, UNSIGNED_BYTE, PixelUnpackData::Slice(Some(pixels)));
+			self.gl.bind_texture(TEXTURE_2D, None);
+		}
+		self.check("glTexSubImage2D");
+	}
+
+	fn deleteTexture(&self, texture: TextureId) {
+		if let Some(texture) = self.textures.remove(texture.0) {
+			unsafe {
+				self.gl.delete_texture(texture);
+			}
+			self.check("glDeleteTextures");
+		}
+	}
+
+	fn bindTexture(&self, unit: u32, texture: Option<TextureId>) {
+		let Some(texture) = self.resolveBinding(&self.textures, texture.map(|t| t.0), "texture") else {
+			return;
+		};
+		unsafe {
+			self.gl.active_texture(TEXTURE0 + unit);
+			self.gl.bind_texture(TEXTURE_2D, texture);
+		}
+		self.check("glBindTexture");
+	}
+
+	fn drawArrays(&self, mode: u32, first: i32, count: i32) {
+		unsafe {
+			self.gl.draw_arrays(mode, first, count);
+		}
+		self.check("glDrawArrays");
+	}
+
+	fn drawElements(&self, mode: u32, count: i32, offset: i32) {
+		unsafe {
+			self.gl.draw_elements(mode, count, UNSIGNED_INT, offset);
+		}
+		self.check("glDrawElements");
+	}
+
+	fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+		unsafe {
+			self.gl.viewport(x, y, width, height);
+		}
+		self.check("glViewport");
+	}
+
+	fn clearColor(&self, r: f32, g: f32, b: f32, a: f32) {
+		unsafe {
+			self.gl.clear_color(r, g, b, a);
+		}
+		self.check("glClearColor");
+	}
+
+	fn clearDepth(&self, depth: f32) {
+		unsafe {
+			self.gl.clear_depth_f32(depth);
+		}
+		self.check("glClearDepthf");
+	}
+
+	fn clear(&self, mask: u32) {
+		unsafe {
+			self.gl.clear(mask);
+		}
+		self.check("glClear");
+	}
+
+	fn enable(&self, capability: u32) {
+		unsafe {
+			self.gl.enable(capability);
+		}
+		self.check("glEnable");
+	}
+
+	fn disable(&self, capability: u32) {
+		unsafe {
+			self.gl.disable(capability);
+		}
+		self.check("glDisable");
+	}
+
+	fn depthFunc(&self, func: u32) {
+		unsafe {
+			self.gl.depth_func(func);
+		}
+		self.check("glDepthFunc");
+	}
+
+	fn blendFunc(&self, src: u32, dst: u32) {
+		unsafe {
+			self.gl.blend_func(src, dst);
+		}
+		self.check("glBlendFunc");
+	}
+
+	fn scissor(&self, x: i32, y: i32, width: i32, height: i32) {
+		unsafe {
+			self.gl.scissor(x, y, width, height);
+		}
+		self.check("glScissor");
+	}
+
+	fn supportsTimerQueries(&self) -> bool {
+		self.timerQueries
+	}
+
+	fn createQuery(&self) -> Result<QueryId, String> {
+		let query = unsafe { self.gl.create_query() }.map_err(|e| format!("Failed to create query: {}", e))?;
+		self.check("glGenQueries");
+		let id = self.nextId();
+		self.queries.insert(id, query);
+		Ok(QueryId(id))
+	}
+
+	fn deleteQuery(&self, query: QueryId) {
+		if let Some(query) = self.queries.remove(query.0) {
+			unsafe {
+				self.gl.delete_query(query);
+			}
+			self.check("glDeleteQueries");
+		}
+	}
+
+	fn beginQuery(&self, target: u32, query: QueryId) {
+		let Some(query) = self.resolve(&self.queries, query.0, "query") else {
+			return;
+		};
+		unsafe {
+			self.gl.begin_query(target, query);
+		}
+		self.check("glBeginQuery");
+	}
+
+	fn endQuery(&self, target: u32) {
+		unsafe {
+			self.gl.end_query(target);
+		}
+		self.check("glEndQuery");
+	}
+
+	fn queryResult(&self, query: QueryId) -> Option<u64> {
+		let query = self.resolve(&self.queries, query.0, "query")?;
+		let available = unsafe { self.gl.get_query_parameter_u32(query, QUERY_RESULT_AVAILABLE) } != 0;
+		self.check("glGetQueryObjectuiv");
+		if !available {
+			return None;
+		}
+		let result = queryResultU64(&self.gl, query);
+		self.check("glGetQueryObjectui64v");
+		Some(result)
+	}
+
+	#[cfg(target_arch = "wasm32")]
+	fn queriesDisjoint(&self) -> bool {
+		let disjoint = unsafe { self.gl.get_parameter_bool(GPU_DISJOINT_EXT) };
+		self.check("glGetBooleanv");
+		disjoint
+	}
+
+	fn setLabel(&self, object: RenderObject, label: &str) {
+		let object = match object {
+			RenderObject::Program(id) => self.resolve(&self.programs, id.0, "program").map(GlObject::Program),
+			RenderObject::Buffer(id) => self.resolve(&self.buffers, id.0, "buffer").map(GlObject::Buffer),
+			RenderObject::VertexArray(id) => self.resolve(&self.vertexArrays, id.0, "vertex array").map(GlObject::VertexArray),
+			RenderObject::Texture(id) => self.resolve(&self.textures, id.0, "texture").map(GlObject::Texture),
+		};
+		if let Some(object) = object {
+			labelObject(&self.gl, object, label);
+		}
+	}
+}
+
+// EXT_disjoint_timer_query, not in glow
+#[cfg(target_arch = "wasm32")]
+const GPU_DISJOINT_EXT: u32 = 0x8FBB;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn supportsTimerQueries(gl: &Context) -> bool {
+	let version = gl.version();
+	let extensions = gl.supported_extensions();
+	if version.is_embedded {
+		extensions.contains("GL_EXT_disjoint_timer_query")
+	} else {
+		(version.major, version.minor) >= (3, 3) || extensions.contains("GL_ARB_timer_query")
+	}
+}
+
+#[cfg(target_arch = "wasm32")]
+fn supportsTimerQueries(gl: &Context) -> bool {
+	gl.supported_extensions().contains("EXT_disjoint_timer_query_webgl2")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn queryResultU64(gl: &Context, query: Query) -> u64 {
+	let mut result = 0u64;
+	unsafe {
+		// glow takes the destination as an offset, which is a pointer without a bound query buffer
+		gl.get_query_parameter_u64_with_offset(query, QUERY_RESULT, &mut result as *mut u64 as usize);
+	}
+	result
+}
+
+// WebGL only hands out 32 bits, a little over 4 seconds of nanoseconds
+#[cfg(target_arch = "wasm32")]
+fn queryResultU64(gl: &Context, query: Query) -> u64 {
+	unsafe { gl.get_query_parameter_u32(query, QUERY_RESULT) as u64 }
+}
+
+fn compileShader(gl: &Context, source: &str, shaderType: u32) -> Result<Shader, String> {
+	unsafe {
+		let shader = gl.create_shader(shaderType).map_err(|e| format!("Failed to create shader: {}", e))?;
+		gl.shader_source(shader, source);
+		gl.compile_shader(shader);
+
+		if !gl.get_shader_compile_status(shader) {
+			let error = gl.get_shader_info_log(shader);
+			gl.delete_shader(shader);
+			return Err(format!("Failed to compile shader: {}", error));
+		}
+		Ok(shader)
+	}
+}
@@ -0,0 +1,250 @@
+#![allow(non_snake_case)]
+
+use std::collections::VecDeque;
+use std::rc::Rc;
+use glow::TIME_ELAPSED;
+use log::{info, warn};
+use crate::render::{QueryId, RenderContext};
+use crate::trace::{chromeTraceJson, traceTime, TraceEvent};
+
+// Frames of queries in flight before the oldest is dropped unread
+const MAX_PENDING_FRAMES: usize = 4;
+const MAX_TRACE_EVENTS: usize = 10_000;
+// Trace row for GPU events
+const GPU_TRACE_TID: u32 = 2;
+
+#[derive(Clone, Debug)]
+pub struct GpuTiming {
+	pub name: String,
+	// Seconds, from the latest resolved frame
+	pub time: f64,
+	// Seconds, smoothed
+	pub average: f64,
+}
+
+struct PassQuery {
+	name: String,
+	query: QueryId,
+	// Trace time the pass was submitted
+	start: f64,
+}
+
+/*
+ * Per-pass GPU timing with TIME_ELAPSED queries, `EXT_disjoint_timer_query_webgl2` on web. Results
+ * are read a few frames later once the GPU has caught up, so nothing stalls. Elapsed queries can't
+ * nest, `begin` ends the previous pass.
+ */
+pub struct GpuProfiler {
+	context: Rc<dyn RenderContext>,
+	pub enabled: bool,
+	supported: bool,
+	free: Vec<QueryId>,
+	frame: Vec<PassQuery>,
+	active: bool,
+	pending: VecDeque<Vec<PassQuery>>,
+	timings: Vec<GpuTiming>,
+	trace: VecDeque<TraceEvent>,
+	destroyed: bool,
+}
+
+#[allow(dead_code)]
+impl GpuProfiler {
+	pub fn new(context: Rc<dyn RenderContext>) -> Self {
+		let supported = context.supportsTimerQueries();
+		if !supported {
+			info!("GPU timer queries are unavailable");
+		}
+		GpuProfiler {
+			context,
+			enabled: true,
+			supported,
+			free: Vec::new(),
+			frame: Vec::new(),
+			active: false,
+			pending: VecDeque::new(),
+			timings: Vec::new(),
+			trace: VecDeque::new(),
+			destroyed: false,
+		}
+	}
+
+	pub fn isSupported(&self) -> bool {
+		self.supported
+	}
+
+	// Reads whatever results have arrived since the last frame
+	pub fn beginFrame(&mut self) {
+		if self.supported {
+			self.resolve();
+		}
+	}
+
+	pub fn begin(&mut self, name: &str) {
+		if !self.enabled || !self.supported {
+			return;
+		}
+		self.end();
+		let query = match self.free.pop() {
+			Some(query) => query,
+			None => match self.context.createQuery() {
+				Ok(query) => query,
+				Err(e) => {
+					warn!("{}", e);
+					return;
+				}
+			},
+		};
+		self.context.beginQuery(TIME_ELAPSED, query);
+		self.frame.push(PassQuery {
+			name: name.to_string(),
+			query,
+			start: traceTime(),
+		});
+		self.active = true;
+	}
+
+	pub fn end(&mut self) {
+		if self.active {
+			self.context.endQuery(TIME_ELAPSED);
+			self.active = false;
+		}
+	}
+
+	pub fn endFrame(&mut self) {
+		self.end();
+		if self.frame.is_empty() {
+			return;
+		}
+		self.pending.push_back(std::mem::take(&mut self.frame));
+		while self.pending.len() > MAX_PENDING_FRAMES {
+			if let Some(dropped) = self.pending.pop_front() {
+				self.free.extend(dropped.into_iter().map(|pass| pass.query));
+			}
+		}
+	}
+
+	fn resolve(&mut self) {
+		// Timings are garbage across a disjoint event (GPU reset, throttling), drop everything in flight
+		if !self.pending.is_empty() && self.context.queriesDisjoint() {
+			for dropped in std::mem::take(&mut self.pending) {
+				self.free.extend(dropped.into_iter().map(|pass| pass.query));
+			}
+			return;
+		}
+
+		// Queries finish in order, the last one being ready means the whole frame is
+		while let Some(frame) = self.pending.front() {
+			let Some(last) = frame.last() else {
+				self.pending.pop_front();
+				continue;
+			};
+			if self.context.queryResult(last.query).is_none() {
+				break;
+			}
+			let Some(frame) = self.pending.pop_front() else {
+				break;
+			};
+			for pass in frame {
+				let nanos = self.context.queryResult(pass.query).unwrap_or(0);
+				self.record(&pass.name, pass.start, nanos as f64 / 1e9);
+				self.free.push(pass.query);
+			}
+		}
+	}
+
+	fn record(&mut self, name: &str, start: f64, time: f64) {
+		match self.timings.iter_mut().find(|t| t.name == name) {
+			Some(timing) => {
+				timing.time = time;
+				timing.average += (time - timing.average) * 0.1;
+			},
+			None => self.timings.push(GpuTiming {
+				name: name.to_string(),
+				time,
+				average: time,
+			}),
+		}
+		// Drawn at the CPU submit time, the GPU runs it some time after
+		if self.trace.len() >= MAX_TRACE_EVENTS {
+			self.trace.pop_front();
+		}
+		self.trace.push_back(TraceEvent::complete(name, "gpu", GPU_TRACE_TID, start, time * 1_000_000.0));
+	}
+
+	// Each pass seen so far in first use order
+	pub fn timings(&self) -> &[GpuTiming] {
+		&self.timings
+	}
+
+	// Seconds, sum of the latest pass times
+	pub fn total(&self) -> f64 {
+		self.timings.iter().map(|t| t.time).sum()
+	}
+
+	pub fn traceEvents(&self) -> impl Iterator<Item = &TraceEvent> {
+		self.trace.iter()
+	}
+
+	// The recorded passes as Chrome trace JSON
+	pub fn chromeTrace(&self) -> Result<String, String> {
+		chromeTraceJson(&self.trace)
+	}
+
+	pub fn clear(&mut self) {
+		self.timings.clear();
+		self.trace.clear();
+	}
+
+	pub fn destroy(&mut self) {
+		if self.destroyed {
+			return;
+		}
+		self.end();
+		let queries = self.free.drain(..)
+			.chain(self.frame.drain(..).map(|pass| pass.query))
+			.chain(self.pending.drain(..).flatten().map(|pass| pass.query));
+		for query in queries {
+			self.context.deleteQuery(query);
+		}
+		self.destroyed = true;
+	}
+}
+
+impl Drop for GpuProfiler {
+	fn drop(&mut self) {
+		self.destroy();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::rc::Rc;
+	use glow::TIME_ELAPSED;
+	use crate::render::{GpuProfiler, QueryId, RecordingContext, RenderCommand};
+
+	#[test]
+	fn passesEndTheOneBefore() {
+		let context = Rc::new(RecordingContext::new());
+		let mut profiler = GpuProfiler::new(context.clone());
+		profiler.beginFrame();
+		profiler.begin("A");
+		profiler.begin("B");
+		profiler.endFrame();
+		assert_eq!(context.take(), [
+			RenderCommand::CreateQuery(QueryId(1)),
+			RenderCommand::BeginQuery { target: TIME_ELAPSED, query: QueryId(1) },
+			RenderCommand::EndQuery(TIME_ELAPSED),
+			RenderCommand::CreateQuery(QueryId(2)),
+			RenderCommand::BeginQuery { target: TIME_ELAPSED, query: QueryId(2) },
+			RenderCommand::EndQuery(TIME_ELAPSED),
+		]);
+
+		// Resolved next frame, queries are reused
+		profiler.beginFrame();
+		let names: Vec<&str> = profiler.timings().iter().map(|t| t.name.as_str()).collect();
+		assert_eq!(names, ["A", "B"]);
+		profiler.begin("A");
+		profiler.endFrame();
+		assert!(!context.take().iter().any(|c| matches!(c, RenderCommand::CreateQuery(_))));
+	}
+}
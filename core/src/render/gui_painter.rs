@@ -0,0 +1,168 @@
+#![allow(non_snake_case)]
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use egui::epaint::{ImageData, Primitive};
+use egui::{ClippedPrimitive, TextureId, TexturesDelta};
+use glow::{BLEND, ONE, ONE_MINUS_SRC_ALPHA, SCISSOR_TEST, TRIANGLES};
+use log::{info, warn};
+use crate::render::{RenderContext, Shader, Texture, TextureFilter, VertexAttrib, VertexBuffer};
+
+/*
+ * Draws egui output through the app's render context
+ *
+ * Shader data:
+ * - float2 pos (points)
+ * - float2 uv
+ * - float4 color (premultiplied)
+ *
+ * Floats: 8
+ */
+pub struct GuiPainter {
+	context: Rc<dyn RenderContext>,
+	shader: Shader,
+	buffer: VertexBuffer,
+	textures: HashMap<TextureId, Texture>,
+	vertices: Vec<f32>,
+	destroyed: bool,
+}
+
+#[cfg(target_arch = "wasm32")]
+const SHADER_VERT: &str = include_str!("../../../resources/shaders/web/gui.vert");
+#[cfg(target_arch = "wasm32")]
+const SHADER_FRAG: &str = include_str!("../../../resources/shaders/web/gui.frag");
+
+#[cfg(not(target_arch = "wasm32"))]
+const SHADER_VERT: &str = include_str!("../../../resources/shaders/native/gui.vert");
+#[cfg(not(target_arch = "wasm32"))]
+const SHADER_FRAG: &str = include_str!("../../../resources/shaders/native/gui.frag");
+
+impl GuiPainter {
+	pub fn new(context: Rc<dyn RenderContext>) -> Result<Self, String> {
+		let shader = Shader::newVertFrag(context.clone(), SHADER_VERT, SHADER_FRAG)?;
+		let buffer = VertexBuffer::new(context.clone(), &shader, &[
+			VertexAttrib::new("i_position", 2),
+			VertexAttrib::new("i_uv", 2),
+			VertexAttrib::new("i_color", 4),
+		], true)?;
+		shader.setLabel("GuiPainter");
+		buffer.setLabel("GuiPainter");
+
+		Ok(GuiPainter {
+			context,
+			shader,
+			buffer,
+			textures: HashMap::new(),
+			vertices: Vec::new(),
+			destroyed: false,
+		})
+	}
+
+	pub fn updateTextures(&mut self, delta: &TexturesDelta) {
+		for (id, imageDelta) in &delta.set {
+			let ImageData::Color(image) = &imageDelta.image;
+			let pixels: Vec<u8> = image.pixels.iter().flat_map(|c| c.to_array()).collect();
+			let (width, height) = (image.size[0] as u32, image.size[1] as u32);
+
+			match imageDelta.pos {
+				Some([x, y]) => match self.textures.get(id) {
+					Some(texture) => texture.update(x as u32, y as u32, width, height, &pixels),
+					None => warn!("Partial update for unknown gui texture {:?}", id),
+				},
+				None => {
+					let filter = match imageDelta.options.magnification {
+						egui::TextureFilter::Nearest => TextureFilter::Nearest,
+						egui::TextureFilter::Linear => TextureFilter::Linear,
+					};
+					match Texture::fromRgba(self.context.clone(), width, height, &pixels, filter) {
+						Ok(texture) => {
+							self.textures.insert(*id, texture);
+						}
+						Err(e) => warn!("Failed to create gui texture: {}", e),
+					}
+				}
+			}
+		}
+	}
+
+	// Textures are freed after painting as the primitives may still reference them
+	pub fn freeTextures(&mut self, delta: &TexturesDelta) {
+		for id in &delta.free {
+			self.textures.remove(id);
+		}
+	}
+
+	// `screenSize` is in physical pixels
+	pub fn paint(&mut self, primitives: &[ClippedPrimitive], pixelsPerPoint: f32, screenSize: (u32, u32)) {
+		let (width, height) = (screenSize.0 as f32, screenSize.1 as f32);
+		if width <= 0.0 || height <= 0.0 {
+			return;
+		}
+
+		self.shader.bind();
+		self.shader.setUniform2f("u_screenSize", width / pixelsPerPoint, height / pixelsPerPoint);
+		self.shader.setUniform1i("u_texture", 0);
+
+		self.context.enable(BLEND);
+		self.context.blendFunc(ONE, ONE_MINUS_SRC_ALPHA);
+		self.context.enable(SCISSOR_TEST);
+		self.buffer.bind();
+
+		for primitive in primitives {
+			let Primitive::Mesh(mesh) = &primitive.primitive else {
+				continue;
+			};
+			let Some(texture) = self.textures.get(&mesh.texture_id) else {
+				continue;
+			};
+
+			// Clip rect is in points with origin top-left, scissor is in pixels from bottom-left
+			let clip = primitive.clip_rect;
+			let minX = (clip.min.x * pixelsPerPoint).round().clamp(0.0, width);
+			let minY = (clip.min.y * pixelsPerPoint).round().clamp(0.0, height);
+			let maxX = (clip.max.x * pixelsPerPoint).round().clamp(minX, width);
+			let maxY = (clip.max.y * pixelsPerPoint).round().clamp(minY, height);
+			if maxX <= minX || maxY <= minY {
+				continue;
+			}
+
+			self.vertices.clear();
+			for vertex in &mesh.vertices {
+				let color = vertex.color.to_array();
+				self.vertices.extend_from_slice(&[
+					vertex.pos.x, vertex.pos.y,
+					vertex.uv.x, vertex.uv.y,
+					color[0] as f32 / 255.0, color[1] as f32 / 255.0, color[2] as f32 / 255.0, color[3] as f32 / 255.0,
+				]);
+			}
+			self.buffer.upload(&self.vertices);
+			self.buffer.uploadIndices(&mesh.indices);
+
+			texture.bind(0);
+			self.context.scissor(minX as i32, (height - maxY) as i32, (maxX - minX) as i32, (maxY - minY) as i32);
+			self.context.drawElements(TRIANGLES, mesh.indices.len() as i32, 0);
+		}
+
+		self.buffer.unbind();
+		self.context.bindTexture(0, None);
+		self.context.disable(SCISSOR_TEST);
+		self.context.disable(BLEND);
+	}
+
+	pub fn destroy(&mut self) {
+		if self.destroyed {
+			return;
+		}
+		info!("Destroying gui painter");
+		self.shader.delete();
+		self.buffer.destroy();
+		self.textures.clear();
+		self.destroyed = true;
+	}
+}
+
+impl Drop for GuiPainter {
+	fn drop(&mut self) {
+		self.destroy();
+	}
+}
@@ -2,19 +2,24 @@
 
 use std::rc::Rc;
 use glam::{Mat4, Vec3};
-use glow::{Buffer, HasContext, VertexArray, ARRAY_BUFFER, DYNAMIC_DRAW, FLOAT, LINES};
+use glow::LINES;
 use log::info;
-use crate::render::Shader;
+use crate::geometry::{Aabb, Frustum};
+use crate::profileScope;
+use crate::render::{RenderContext, Shader, VertexAttrib, VertexBuffer};
 
 pub struct LineRenderer {
-    gl: Rc<glow::Context>,
+    context: Rc<dyn RenderContext>,
     vec: Vec<f32>,
     shader: Shader,
-    vao: VertexArray,
-    vbo: Buffer,
+    buffer: VertexBuffer,
     floatsPushed: usize,
     lastFloatsPushed: usize,
     pub enabled: bool,
+    // Lines outside are skipped when pushed
+    pub cullFrustum: Option<Frustum>,
+    culled: usize,
+    pub lastCulled: usize,
     destroyed: bool,
 }
 
@@ -29,7 +34,6 @@ pub struct LineRenderer {
  * Bytes: 48
  */
 const FLOATS: usize = 6;
-const FLOAT_SIZE: usize = size_of::<f32>();
 
 #[cfg(target_arch = "wasm32")]
 const SHADER_VERT: &str = include_str!("../../../resources/shaders/web/line_renderer.vert");
@@ -42,74 +46,39 @@ const SHADER_VERT: &str = include_str!("../../../resources/shaders/native/line_r
 const SHADER_FRAG: &str = include_str!("../../../resources/shaders/native/line_renderer.frag");
 
 impl LineRenderer {
-    pub fn new(gl: Rc<glow::Context>, capacity: usize) -> Result<Self, String> {
-        unsafe {
-			let vec = Vec::with_capacity(capacity);
-            let shader = Shader::newVertFrag(gl.clone(), SHADER_VERT, SHADER_FRAG)?;
-			
-			let vao = gl.create_vertex_array().map_err(|e| format!("Failed to create vertex array: {}", e))?;
-			let vbo = gl.create_buffer().map_err(|e| format!("Failed to create buffer object: {}", e))?;
-			
-			gl.bind_vertex_array(Some(vao));
-			gl.bind_buffer(ARRAY_BUFFER, Some(vbo));
-			gl.buffer_data_u8_slice(ARRAY_BUFFER, bytemuck::cast_slice(&vec), DYNAMIC_DRAW);
-			
-			let locPos = shader.getAttribLocation("i_position").unwrap();
-			let locCol = shader.getAttribLocation("i_color").unwrap();
-			
-			let mut offset: usize = 0;
-			let stride = (6 * FLOAT_SIZE) as i32;
-			gl.vertex_attrib_pointer_f32(locPos, 3, FLOAT, false, stride, offset as i32);
-			gl.enable_vertex_attrib_array(locPos);
-			
-			offset += 3 * FLOAT_SIZE;
-			gl.vertex_attrib_pointer_f32(locCol, 3, FLOAT, false, stride, offset as i32);
-			gl.enable_vertex_attrib_array(locCol);
-			
-			gl.bind_buffer(ARRAY_BUFFER, None);
-			gl.bind_vertex_array(None);
-
-            // let vao = gl.create_vertex_array().map_err(|e| format!("Failed to create vertex array: {}", e))?;
-            // let vbo = gl.create_named_buffer().map_err(|e| format!("Failed to create buffer object: {}", e))?;
-            // gl.bind_vertex_array(Some(vao));
-			//
-            // // gl.named_buffer_data_size(vbo, (capacity * FLOAT_SIZE) as i32, DYNAMIC_DRAW);
-            // gl.named_buffer_data_u8_slice(vbo, bytemuck::cast_slice(&vec), DYNAMIC_DRAW);
-            // gl.vertex_array_vertex_buffer(vao, 0, Some(vbo), 0, (FLOATS * FLOAT_SIZE) as i32);
-			//
-            // let locPos = shader.getAttribLocation("i_position").unwrap();
-            // let locCol = shader.getAttribLocation("i_color").unwrap();
-			//
-            // let mut offset: usize = 0;
-            // gl.vertex_array_attrib_format_f32(vao, locPos, 3, FLOAT, false, offset as u32);
-            // gl.vertex_array_attrib_binding_f32(vao, locPos, 0);
-            // offset += 3 * FLOAT_SIZE;
-			//
-            // gl.vertex_array_attrib_format_f32(vao, locCol, 3, FLOAT, false, offset as u32);
-            // gl.vertex_array_attrib_binding_f32(vao, locCol, 0);
-            // // offset += 3 * FLOAT_SIZE;
-			//
-            // gl.enable_vertex_array_attrib(vao, locPos);
-            // gl.enable_vertex_array_attrib(vao, locCol);
-
-            Ok(LineRenderer {
-                gl,
-                vec,
-                shader,
-                vao,
-                vbo,
-                floatsPushed: 0,
-                lastFloatsPushed: capacity,
-                enabled: true,
-                destroyed: false,
-            })
-        }
+    pub fn new(context: Rc<dyn RenderContext>, capacity: usize) -> Result<Self, String> {
+		let vec = Vec::with_capacity(capacity);
+        let shader = Shader::newVertFrag(context.clone(), SHADER_VERT, SHADER_FRAG)?;
+		let buffer = VertexBuffer::new(context.clone(), &shader, &[
+			VertexAttrib::new("i_position", 3),
+			VertexAttrib::new("i_color", 3),
+		], false)?;
+		shader.setLabel("LineRenderer");
+		buffer.setLabel("LineRenderer");
+
+        Ok(LineRenderer {
+            context,
+            vec,
+            shader,
+            buffer,
+            floatsPushed: 0,
+            lastFloatsPushed: capacity,
+            enabled: true,
+            cullFrustum: None,
+            culled: 0,
+            lastCulled: 0,
+            destroyed: false,
+        })
     }
 
     pub fn pushLine(&mut self, pos1: Vec3, color1: Vec3, pos2: Vec3, color2: Vec3) {
         if !self.enabled {
             return;
         }
+        if let Some(frustum) = &self.cullFrustum && !frustum.intersectsAabb(&Aabb::new(pos1, pos2)) {
+            self.culled += 1;
+            return;
+        }
         self.vec.push(pos1.x);
         self.vec.push(pos1.y);
         self.vec.push(pos1.z);
@@ -128,6 +97,9 @@ impl LineRenderer {
     }
 
     pub fn drawFlush(&mut self, pvMatrix: &Mat4) {
+        profileScope!("LineRenderer::drawFlush");
+        self.lastCulled = self.culled;
+        self.culled = 0;
         if self.vec.len() < FLOATS * 2 || self.floatsPushed < FLOATS * 2 {
             return;
         }
@@ -135,27 +107,13 @@ impl LineRenderer {
         self.shader.bind();
         self.shader.setMatrix4f("u_pvm", pvMatrix);
 
-        unsafe {
-			self.gl.bind_vertex_array(Some(self.vao));
-			self.gl.bind_buffer(ARRAY_BUFFER, Some(self.vbo));
-			
-			self.gl.buffer_data_u8_slice(ARRAY_BUFFER, bytemuck::cast_slice(&self.vec), DYNAMIC_DRAW);
-			
-            // if self.floatsPushed > self.lastFloatsPushed {
-			// 	self.gl.buffer_data_u8_slice(ARRAY_BUFFER, bytemuck::cast_slice(&self.vec), DYNAMIC_DRAW);
-            //     // self.gl.named_buffer_data_u8_slice(self.vbo, bytemuck::cast_slice(&self.vec), DYNAMIC_DRAW);
-            // } else {
-			// 	self.gl.buffer_sub_data_u8_slice(ARRAY_BUFFER, 0, bytemuck::cast_slice(&self.vec));
-            //     // self.gl.named_buffer_sub_data_u8_slice(self.vbo, 0, bytemuck::cast_slice(&self.vec));
-            // }
-
-            let drawCount = self.vec.len() / FLOATS;
-            // info!("drawCount: {}", drawCount);
-            self.gl.draw_arrays(LINES, 0, drawCount as i32);
-			
-			self.gl.bind_buffer(ARRAY_BUFFER, None);
-			self.gl.bind_vertex_array(None);
-        }
+		self.buffer.bind();
+		self.buffer.upload(&self.vec);
+
+        let drawCount = self.vec.len() / FLOATS;
+        // info!("drawCount: {}", drawCount);
+        self.context.drawArrays(LINES, 0, drawCount as i32);
+		self.buffer.unbind();
 
         self.vec.clear();
         self.lastFloatsPushed = self.floatsPushed;
@@ -168,10 +126,7 @@ impl LineRenderer {
         }
 		info!("Destroying line renderer");
         self.shader.delete();
-        unsafe {
-            self.gl.delete_buffer(self.vbo);
-            self.gl.delete_vertex_array(self.vao);
-        }
+        self.buffer.destroy();
         self.destroyed = true;
     }
 }
@@ -180,4 +135,57 @@ impl Drop for LineRenderer {
     fn drop(&mut self) {
         self.destroy();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use glam::{vec3, Mat4, Vec3};
+    use glow::{ARRAY_BUFFER, LINES};
+    use crate::geometry::Frustum;
+    use crate::render::{LineRenderer, RecordingContext, RenderCommand, Uniform};
+
+    #[test]
+    fn drawFlushUploadsPushedLines() {
+        let context = Rc::new(RecordingContext::new());
+        let mut lines = LineRenderer::new(context.clone(), 16).unwrap();
+        context.clear();
+
+        lines.pushLine(Vec3::ZERO, Vec3::X, Vec3::ONE, Vec3::Y);
+        lines.pushLine(vec3(1.0, 2.0, 3.0), Vec3::Z, vec3(4.0, 5.0, 6.0), Vec3::ONE);
+        let pvm = Mat4::from_translation(vec3(1.0, 0.0, 0.0));
+        lines.drawFlush(&pvm);
+
+        assert_eq!(context.drawCount(), 1);
+        let commands = context.commands();
+        assert!(commands.contains(&RenderCommand::DrawArrays { mode: LINES, first: 0, count: 4 }));
+        assert!(commands.iter().any(|c| matches!(c, RenderCommand::SetUniform { name, value, .. } if name == "u_pvm" && *value == Uniform::Mat4(pvm))));
+        assert_eq!(context.lastUploadFloats(ARRAY_BUFFER).unwrap(), vec![
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0,
+            1.0, 1.0, 1.0, 0.0, 1.0, 0.0,
+            1.0, 2.0, 3.0, 0.0, 0.0, 1.0,
+            4.0, 5.0, 6.0, 1.0, 1.0, 1.0,
+        ]);
+
+        // Nothing pushed since, nothing drawn
+        context.clear();
+        lines.drawFlush(&pvm);
+        assert_eq!(context.drawCount(), 0);
+    }
+
+    #[test]
+    fn culledLinesAreNotDrawn() {
+        let context = Rc::new(RecordingContext::new());
+        let mut lines = LineRenderer::new(context.clone(), 16).unwrap();
+        context.clear();
+
+        lines.cullFrustum = Some(Frustum::fromMatrix(&Mat4::IDENTITY, (-1.0, 1.0)));
+        lines.pushLine(Vec3::ZERO, Vec3::ONE, Vec3::X, Vec3::ONE);
+        lines.pushLine(vec3(5.0, 5.0, 0.0), Vec3::ONE, vec3(6.0, 5.0, 0.0), Vec3::ONE);
+        lines.drawFlush(&Mat4::IDENTITY);
+
+        assert_eq!(lines.lastCulled, 1);
+        assert!(context.commands().contains(&RenderCommand::DrawArrays { mode: LINES, first: 0, count: 2 }));
+        assert_eq!(context.lastUploadFloats(ARRAY_BUFFER).unwrap().len(), 12);
+    }
+}
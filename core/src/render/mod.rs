@@ -1,5 +1,38 @@
+mod atlas;
+#[cfg(not(target_arch = "wasm32"))]
+mod clip_control;
+mod context;
+mod debug_font;
+mod debug_overlay;
+mod font;
+mod gl_debug;
+mod glow_context;
+mod gpu_profiler;
+mod gui_painter;
 mod line_renderer;
+mod recording_context;
 mod shader;
+mod sprite_batch;
+mod sprite_sheet;
+mod text_renderer;
+mod texture;
+mod vertex_buffer;
 
+pub use self::atlas::{AtlasBuilder, AtlasRect, Image, RectPacker, TextureAtlas};
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::clip_control::enableClipControl;
+pub use self::context::{BufferId, ProgramId, QueryId, RenderContext, RenderObject, ShaderStage, TextureId, Uniform, VertexArrayId};
+pub use self::debug_overlay::{recordDebugLog, DebugOverlay};
+pub use self::font::{Font, Glyph};
+pub use self::gl_debug::{checkGlError, enableGlDebug, isGlDebugEnabled, labelObject, GlDebugSeverity, GlObject};
+pub use self::glow_context::GlowContext;
+pub use self::gpu_profiler::{GpuProfiler, GpuTiming};
+pub use self::gui_painter::GuiPainter;
 pub use self::line_renderer::LineRenderer;
-pub use self::shader::Shader;
\ No newline at end of file
+pub use self::recording_context::{RecordingContext, RenderCommand};
+pub use self::shader::Shader;
+pub use self::sprite_batch::{Sprite, SpriteBatch};
+pub use self::sprite_sheet::{Animation, AnimationMode, AnimationPlayer, SpriteFrame, SpriteSheet};
+pub use self::text_renderer::{layoutText, PositionedGlyph, TextAlign, TextLayout, TextRenderer, TextStyle};
+pub use self::texture::{Texture, TextureFilter};
+pub use self::vertex_buffer::{VertexAttrib, VertexBuffer};
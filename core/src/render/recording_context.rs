@@ -0,0 +1,263 @@
+#![allow(non_snake_case)]
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use crate::render::{BufferId, ProgramId, QueryId, RenderContext, ShaderStage, TextureFilter, TextureId, Uniform, VertexArrayId};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum RenderCommand {
+	CreateProgram(ProgramId),
+	DeleteProgram(ProgramId),
+	UseProgram(Option<ProgramId>),
+	SetUniform { program: ProgramId, name: String, value: Uniform },
+	CreateVertexArray(VertexArrayId),
+	DeleteVertexArray(VertexArrayId),
+	BindVertexArray(Option<VertexArrayId>),
+	VertexAttribPointer { location: u32, size: i32, stride: i32, offset: i32 },
+	EnableVertexAttribArray(u32),
+	CreateBuffer(BufferId),
+	DeleteBuffer(BufferId),
+	BindBuffer { target: u32, buffer: Option<BufferId> },
+	BufferData { target: u32, data: Vec<u8>, usage: u32 },
+	CreateTexture { texture: TextureId, width: u32, height: u32, filter: TextureFilter },
+	UpdateTexture { texture: TextureId, x: u32, y: u32, width: u32, height: u32 },
+	DeleteTexture(TextureId),
+	BindTexture { unit: u32, texture: Option<TextureId> },
+	DrawArrays { mode: u32, first: i32, count: i32 },
+	DrawElements { mode: u32, count: i32, offset: i32 },
+	Viewport { x: i32, y: i32, width: i32, height: i32 },
+	ClearColor([f32; 4]),
+	ClearDepth(f32),
+	Clear(u32),
+	Enable(u32),
+	Disable(u32),
+	DepthFunc(u32),
+	BlendFunc { src: u32, dst: u32 },
+	Scissor { x: i32, y: i32, width: i32, height: i32 },
+	CreateQuery(QueryId),
+	DeleteQuery(QueryId),
+	BeginQuery { target: u32, query: QueryId },
+	EndQuery(u32),
+}
+
+/*
+ * `RenderContext` that keeps the commands in memory instead of drawing, for tests without a GPU.
+ * Programs always link, attributes get locations in the order they're first looked up.
+ * Timer queries are supported and finish instantly taking no time.
+ */
+#[derive(Default)]
+pub struct RecordingContext {
+	commands: RefCell<Vec<RenderCommand>>,
+	nextId: Cell<u32>,
+	attribs: RefCell<HashMap<ProgramId, Vec<String>>>,
+}
+
+#[allow(dead_code)]
+impl RecordingContext {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn commands(&self) -> Vec<RenderCommand> {
+		self.commands.borrow().clone()
+	}
+
+	// Returns the commands so far and starts over
+	pub fn take(&self) -> Vec<RenderCommand> {
+		std::mem::take(&mut self.commands.borrow_mut())
+	}
+
+	pub fn clear(&self) {
+		self.commands.borrow_mut().clear();
+	}
+
+	pub fn drawCount(&self) -> usize {
+		self.commands.borrow().iter()
+			.filter(|c| matches!(c, RenderCommand::DrawArrays { .. } | RenderCommand::DrawElements { .. }))
+			.count()
+	}
+
+	// Data of the latest upload to `target`, read as floats
+	pub fn lastUploadFloats(&self, target: u32) -> Option<Vec<f32>> {
+		self.commands.borrow().iter().rev().find_map(|c| match c {
+			RenderCommand::BufferData { target: t, data, .. } if *t == target => {
+				Some(data.chunks_exact(4).map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]])).collect())
+			},
+			_ => None,
+		})
+	}
+
+	fn push(&self, command: RenderCommand) {
+		self.commands.borrow_mut().push(command);
+	}
+
+	fn nextId(&self) -> u32 {
+		let id = self.nextId.get() + 1;
+		self.nextId.set(id);
+		id
+	}
+}
+
+impl RenderContext for RecordingContext {
+	fn createProgram(&self, stages: &[(ShaderStage, &str)]) -> Result<ProgramId, String> {
+		if stages.is_empty() {
+			return Err("Failed to link program: no shader stages".to_string());
+		}
+		let program = ProgramId(self.nextId());
+		self.push(RenderCommand::CreateProgram(program));
+		Ok(program)
+	}
+
+	fn deleteProgram(&self, program: ProgramId) {
+		self.push(RenderCommand::DeleteProgram(program));
+	}
+
+	fn useProgram(&self, program: Option<ProgramId>) {
+		self.push(RenderCommand::UseProgram(program));
+	}
+
+	fn attribLocation(&self, program: ProgramId, name: &str) -> Option<u32> {
+		let mut attribs = self.attribs.borrow_mut();
+		let names = attribs.entry(program).or_default();
+		let location = match names.iter().position(|n| n == name) {
+			Some(location) => location,
+			None => {
+				names.push(name.to_string());
+				names.len() - 1
+			}
+		};
+		Some(location as u32)
+	}
+
+	fn setUniform(&self, program: ProgramId, name: &str, value: Uniform) {
+		self.push(RenderCommand::SetUniform { program, name: name.to_string(), value });
+	}
+
+	fn createVertexArray(&self) -> Result<VertexArrayId, String> {
+		let vao = VertexArrayId(self.nextId());
+		self.push(RenderCommand::CreateVertexArray(vao));
+		Ok(vao)
+	}
+
+	fn deleteVertexArray(&self, vao: VertexArrayId) {
+		self.push(RenderCommand::DeleteVertexArray(vao));
+	}
+
+	fn bindVertexArray(&self, vao: Option<VertexArrayId>) {
+		self.push(RenderCommand::BindVertexArray(vao));
+	}
+
+	fn vertexAttribPointer(&self, location: u32, size: i32, stride: i32, offset: i32) {
+		self.push(RenderCommand::VertexAttribPointer { location, size, stride, offset });
+	}
+
+	fn enableVertexAttribArray(&self, location: u32) {
+		self.push(RenderCommand::EnableVertexAttribArray(location));
+	}
+
+	fn createBuffer(&self) -> Result<BufferId, String> {
+		let buffer = BufferId(self.nextId());
+		self.push(RenderCommand::CreateBuffer(buffer));
+		Ok(buffer)
+	}
+
+	fn deleteBuffer(&self, buffer: BufferId) {
+		self.push(RenderCommand::DeleteBuffer(buffer));
+	}
+
+	fn bindBuffer(&self, target: u32, buffer: Option<BufferId>) {
+		self.push(RenderCommand::BindBuffer { target, buffer });
+	}
+
+	fn bufferData(&self, target: u32, data: &[u8], usage: u32) {
+		self.push(RenderCommand::BufferData { target, data: data.to_vec(), usage });
+	}
+
+	fn createTexture(&self, width: u32, height: u32, _pixels: &[u8], filter: TextureFilter) -> Result<TextureId, String> {
+		let texture = TextureId(self.nextId());
+		self.push(RenderCommand::CreateTexture { texture, width, height, filter });
+		Ok(texture)
+	}
+
+	fn updateTexture(&self, texture: TextureId, x: u32, y: u32, width: u32, height: u32, _pixels: &[u8]) {
+		self.push(RenderCommand::UpdateTexture { texture, x, y, width, height });
+	}
+
+	fn deleteTexture(&self, texture: TextureId) {
+		self.push(RenderCommand::DeleteTexture(texture));
+	}
+
+	fn bindTexture(&self, unit: u32, texture: Option<TextureId>) {
+		self.push(RenderCommand::BindTexture { unit, texture });
+	}
+
+	fn drawArrays(&self, mode: u32, first: i32, count: i32) {
+		self.push(RenderCommand::DrawArrays { mode, first, count });
+	}
+
+	fn drawElements(&self, mode: u32, count: i32, offset: i32) {
+		self.push(RenderCommand::DrawElements { mode, count, offset });
+	}
+
+	fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+		self.push(RenderCommand::Viewport { x, y, width, height });
+	}
+
+	fn clearColor(&self, r: f32, g: f32, b: f32, a: f32) {
+		self.push(RenderCommand::ClearColor([r, g, b, a]));
+	}
+
+	fn clearDepth(&self, depth: f32) {
+		self.push(RenderCommand::ClearDepth(depth));
+	}
+
+	fn clear(&self, mask: u32) {
+		self.push(RenderCommand::Clear(mask));
+	}
+
+	fn enable(&self, capability: u32) {
+		self.push(RenderCommand::Enable(capability));
+	}
+
+	fn disable(&self, capability: u32) {
+		self.push(RenderCommand::Disable(capability));
+	}
+
+	fn depthFunc(&self, func: u32) {
+		self.push(RenderCommand::DepthFunc(func));
+	}
+
+	fn blendFunc(&self, src: u32, dst: u32) {
+		self.push(RenderCommand::BlendFunc { src, dst });
+	}
+
+	fn scissor(&self, x: i32, y: i32, width: i32, height: i32) {
+		self.push(RenderCommand::Scissor { x, y, width, height });
+	}
+
+	fn supportsTimerQueries(&self) -> bool {
+		true
+	}
+
+	fn createQuery(&self) -> Result<QueryId, String> {
+		let query = QueryId(self.nextId());
+		self.push(RenderCommand::CreateQuery(query));
+		Ok(query)
+	}
+
+	fn deleteQuery(&self, query: QueryId) {
+		self.push(RenderCommand::DeleteQuery(query));
+	}
+
+	fn beginQuery(&self, target: u32, query: QueryId) {
+		self.push(RenderCommand::BeginQuery { target, query });
+	}
+
+	fn endQuery(&self, target: u32) {
+		self.push(RenderCommand::EndQuery(target));
+	}
+
+	fn queryResult(&self, _query: QueryId) -> Option<u64> {
+		Some(0)
+	}
+}
@@ -1,106 +1,69 @@
 #![allow(non_snake_case)]
 
 use std::rc::Rc;
-use std::str;
-use glam::{Mat4, Vec2, Vec3, Vec4};
-use glow::{Context, HasContext, Program, FRAGMENT_SHADER, GEOMETRY_SHADER, VERTEX_SHADER};
+use glam::{vec2, vec3, vec4, Mat4, Vec2, Vec3, Vec4};
+use crate::render::{ProgramId, RenderContext, RenderObject, ShaderStage, Uniform};
 
 pub struct Shader {
-    gl: Rc<Context>,
-    pub program: Program,
+    context: Rc<dyn RenderContext>,
+    pub program: ProgramId,
 }
 
 #[allow(dead_code)]
 impl Shader {
-    pub fn newVertFrag(gl: Rc<Context>, vertSource: &str, fragSource: &str) -> Result<Self, String> {
-        unsafe {
-            let program = gl.create_program().map_err(|e| format!("Failed to create program: {}", e))?;
+    pub fn newVertFrag(context: Rc<dyn RenderContext>, vertSource: &str, fragSource: &str) -> Result<Self, String> {
+        let program = context.createProgram(&[
+            (ShaderStage::Vertex, vertSource),
+            (ShaderStage::Fragment, fragSource),
+        ])?;
 
-            let vs = compileShader(&gl, vertSource, VERTEX_SHADER)?;
-            let fs = compileShader(&gl, fragSource, FRAGMENT_SHADER)?;
-
-            gl.attach_shader(program, vs);
-            gl.attach_shader(program, fs);
-
-            // #[cfg(not(target_arch = "wasm32"))]
-            // gl.bind_frag_data_location(program, glow::COLOR_ATTACHMENT0, "o_color");
-            gl.link_program(program);
-
-            gl.delete_shader(vs);
-            gl.delete_shader(fs);
-
-            Ok(Shader {
-                gl,
-                program
-            })
-        }
+        Ok(Shader {
+            context,
+            program
+        })
     }
 
-    pub fn newVertGeomFrag(gl: Rc<Context>, vertexPath: &str, geometryPath: &str, fragmentPath: &str) -> Result<Self, String> {
-        unsafe {
-            let program = gl.create_program().map_err(|e| format!("Failed to create program: {}", e))?;
-
-            let vs = compileShader(&gl, vertexPath, VERTEX_SHADER)?;
-            let gs = compileShader(&gl, geometryPath, GEOMETRY_SHADER)?;
-            let fs = compileShader(&gl, fragmentPath, FRAGMENT_SHADER)?;
-
-            gl.attach_shader(program, vs);
-            gl.attach_shader(program, gs);
-            gl.attach_shader(program, fs);
+    pub fn newVertGeomFrag(context: Rc<dyn RenderContext>, vertexPath: &str, geometryPath: &str, fragmentPath: &str) -> Result<Self, String> {
+        let program = context.createProgram(&[
+            (ShaderStage::Vertex, vertexPath),
+            (ShaderStage::Geometry, geometryPath),
+            (ShaderStage::Fragment, fragmentPath),
+        ])?;
 
-            // #[cfg(not(target_arch = "wasm32"))]
-            // gl.bind_frag_data_location(program, glow::COLOR_ATTACHMENT0, "o_color");
-            gl.link_program(program);
-
-            gl.delete_shader(vs);
-            gl.delete_shader(gs);
-            gl.delete_shader(fs);
+        Ok(Shader {
+            context,
+            program
+        })
+    }
 
-            Ok(Shader {
-                gl,
-                program
-            })
-        }
+    // Name shown in GL debug messages and capture tools
+    pub fn setLabel(&self, label: &str) {
+        self.context.setLabel(RenderObject::Program(self.program), label);
     }
 
     pub fn bind(&self) {
-        unsafe {
-            self.gl.use_program(Some(self.program));
-        }
+        self.context.useProgram(Some(self.program));
     }
 
     pub fn delete(&self) {
-        unsafe {
-            self.gl.delete_program(self.program);
-        }
+        self.context.deleteProgram(self.program);
     }
 
     pub fn getAttribLocation(&self, name: &str) -> Option<u32> {
-        unsafe {
-            self.gl.get_attrib_location(self.program, name)
-        }
+        self.context.attribLocation(self.program, name)
     }
 
     // Uniforms
     pub fn setUniform1i(&self, name: &str, value: i32) {
-        unsafe {
-            let loc = Some(&self.gl.get_uniform_location(self.program, name).unwrap());
-            self.gl.uniform_1_i32(loc, value);
-        }
+        self.context.setUniform(self.program, name, Uniform::Int(value));
     }
 
     pub fn setUniform1ui(&self, name: &str, value: u32) {
-        unsafe {
-            let loc = Some(&self.gl.get_uniform_location(self.program, name).unwrap());
-            self.gl.uniform_1_u32(loc, value);
-        }
+        self.context.setUniform(self.program, name, Uniform::UInt(value));
     }
 
     pub fn setUniform1f(&self, name: &str, value: f32) {
-        unsafe {
-            let loc = Some(&self.gl.get_uniform_location(self.program, name).unwrap());
-            self.gl.uniform_1_f32(loc, value);
-        }
+        self.context.setUniform(self.program, name, Uniform::Float(value));
     }
 
     pub fn setUniform2fv(&self, name: &str, value: &Vec2) {
@@ -108,10 +71,7 @@ impl Shader {
     }
 
     pub fn setUniform2f(&self, name: &str, x: f32, y: f32) {
-        unsafe {
-            let loc = Some(&self.gl.get_uniform_location(self.program, name).unwrap());
-            self.gl.uniform_2_f32(loc, x, y);
-        }
+        self.context.setUniform(self.program, name, Uniform::Vec2(vec2(x, y)));
     }
 
     pub fn setUniform3fv(&self, name: &str, value: &Vec3) {
@@ -119,10 +79,7 @@ impl Shader {
     }
 
     pub fn setUniform3f(&self, name: &str, x: f32, y: f32, z: f32) {
-        unsafe {
-            let loc = Some(&self.gl.get_uniform_location(self.program, name).unwrap());
-            self.gl.uniform_3_f32(loc, x, y, z);
-        }
+        self.context.setUniform(self.program, name, Uniform::Vec3(vec3(x, y, z)));
     }
 
     pub fn setUniform4fv(&self, name: &str, value: &Vec4) {
@@ -130,36 +87,11 @@ impl Shader {
     }
 
     pub fn setUniform4f(&self, name: &str, x: f32, y: f32, z: f32, w: f32) {
-        unsafe {
-            let loc = Some(&self.gl.get_uniform_location(self.program, name).unwrap());
-            self.gl.uniform_4_f32(loc, x, y, z, w);
-        }
+        self.context.setUniform(self.program, name, Uniform::Vec4(vec4(x, y, z, w)));
     }
 
     pub fn setMatrix4f(&self, name: &str, mat: &Mat4) {
-        unsafe {
-            let loc = Some(&self.gl.get_uniform_location(self.program, name).unwrap());
-            self.gl.uniform_matrix_4_f32_slice(loc, false, &mat.to_cols_array());
-        }
-    }
-}
-
-fn compileShader(gl: &Context, source: &str, shaderType: u32) -> Result<glow::Shader, String> {
-    // #[cfg(target_arch = "wasm32")]
-    // let src = include_str!(path);
-    // #[cfg(not(target_arch = "wasm32"))]
-    // let src = fs::read_to_string(path).map_err(|e| format!("Failed to read shader file ({}): {}", path, e))?;
-
-    unsafe {
-        let shader = gl.create_shader(shaderType).map_err(|e| format!("Failed to create shader: {}", e))?;
-        gl.shader_source(shader, source);
-        gl.compile_shader(shader);
-
-        if !gl.get_shader_compile_status(shader) {
-            let error = gl.get_shader_info_log(shader);
-            return Err(format!("Failed to compile shader: {}", error));
-        }
-        Ok(shader)
+        self.context.setUniform(self.program, name, Uniform::Mat4(*mat));
     }
 }
 
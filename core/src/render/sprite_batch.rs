@@ -0,0 +1,319 @@
+#![allow(non_snake_case)]
+
+use std::rc::Rc;
+use glam::{vec2, Mat4, Vec2, Vec3, Vec4};
+use glow::{BLEND, ONE_MINUS_SRC_ALPHA, SRC_ALPHA, TRIANGLES};
+use log::info;
+use crate::geometry::{Aabb, Frustum};
+use crate::profileScope;
+use crate::render::{RenderContext, Shader, Texture, TextureId, VertexAttrib, VertexBuffer};
+
+#[derive(Copy, Clone)]
+pub struct Sprite {
+	pub pos: Vec2,
+	pub size: Vec2,
+	// Pivot for rotation/scale, (0, 0) bottom-left, (1, 1) top-right
+	pub origin: Vec2,
+	// Radians
+	pub rotation: f32,
+	pub scale: Vec2,
+	// (u0, v0, u1, v1), v0 is the top row of the image
+	pub uv: Vec4,
+	pub tint: Vec4,
+	// Lower layers are drawn first, also written to z
+	pub layer: f32,
+}
+
+impl Default for Sprite {
+	fn default() -> Sprite {
+		Sprite {
+			pos: Vec2::ZERO,
+			size: Vec2::ONE,
+			origin: vec2(0.5, 0.5),
+			rotation: 0.0,
+			scale: Vec2::ONE,
+			uv: Vec4::new(0.0, 0.0, 1.0, 1.0),
+			tint: Vec4::ONE,
+			layer: 0.0,
+		}
+	}
+}
+
+struct Quad {
+	texture: TextureId,
+	layer: f32,
+	vertices: [f32; FLOATS * 4],
+}
+
+pub struct SpriteBatch {
+	context: Rc<dyn RenderContext>,
+	quads: Vec<Quad>,
+	vertices: Vec<f32>,
+	indices: Vec<u32>,
+	shader: Shader,
+	buffer: VertexBuffer,
+	pub enabled: bool,
+	// Lets quads in a layer be regrouped by texture, only for sprites that don't overlap or are opaque
+	pub unordered: bool,
+	pub lastDrawCalls: usize,
+	pub lastQuadCount: usize,
+	// Quads outside are skipped when pushed
+	pub cullFrustum: Option<Frustum>,
+	culled: usize,
+	pub lastCulled: usize,
+	destroyed: bool,
+}
+
+/*
+ * Shader data:
+ * - float3 pos
+ * - float2 uv
+ * - float4 color
+ *
+ * Floats: 9
+ * Bytes: 36
+ */
+const FLOATS: usize = 9;
+
+#[cfg(target_arch = "wasm32")]
+const SHADER_VERT: &str = include_str!("../../../resources/shaders/web/sprite_batch.vert");
+#[cfg(target_arch = "wasm32")]
+const SHADER_FRAG: &str = include_str!("../../../resources/shaders/web/sprite_batch.frag");
+
+#[cfg(not(target_arch = "wasm32"))]
+const SHADER_VERT: &str = include_str!("../../../resources/shaders/native/sprite_batch.vert");
+#[cfg(not(target_arch = "wasm32"))]
+const SHADER_FRAG: &str = include_str!("../../../resources/shaders/native/sprite_batch.frag");
+
+#[allow(dead_code)]
+impl SpriteBatch {
+	pub fn new(context: Rc<dyn RenderContext>, capacity: usize) -> Result<Self, String> {
+		let shader = Shader::newVertFrag(context.clone(), SHADER_VERT, SHADER_FRAG)?;
+		let buffer = VertexBuffer::new(context.clone(), &shader, &[
+			VertexAttrib::new("i_position", 3),
+			VertexAttrib::new("i_uv", 2),
+			VertexAttrib::new("i_color", 4),
+		], true)?;
+		shader.setLabel("SpriteBatch");
+		buffer.setLabel("SpriteBatch");
+
+		Ok(SpriteBatch {
+			context,
+			quads: Vec::with_capacity(capacity),
+			vertices: Vec::with_capacity(capacity * FLOATS * 4),
+			indices: Vec::with_capacity(capacity * 6),
+			shader,
+			buffer,
+			enabled: true,
+			unordered: false,
+			lastDrawCalls: 0,
+			lastQuadCount: 0,
+			cullFrustum: None,
+			culled: 0,
+			lastCulled: 0,
+			destroyed: false,
+		})
+	}
+
+	pub fn pushSprite(&mut self, texture: &Texture, sprite: &Sprite) {
+		if !self.enabled {
+			return;
+		}
+		let (sin, cos) = sprite.rotation.sin_cos();
+		let size = sprite.size * sprite.scale;
+		let corner = |x: f32, y: f32| -> Vec3 {
+			let local = (vec2(x, y) - sprite.origin) * size;
+			let rotated = vec2(local.x * cos - local.y * sin, local.x * sin + local.y * cos);
+			(sprite.pos + rotated).extend(sprite.layer)
+		};
+		let corners = [corner(0.0, 0.0), corner(1.0, 0.0), corner(1.0, 1.0), corner(0.0, 1.0)];
+		self.pushQuadRaw(texture.texture, corners, sprite.uv, sprite.tint, sprite.layer);
+	}
+
+	// Corners are bottom-left, bottom-right, top-right, top-left
+	pub fn pushQuad(&mut self, texture: &Texture, corners: [Vec3; 4], uv: Vec4, tint: Vec4, layer: f32) {
+		if !self.enabled {
+			return;
+		}
+		self.pushQuadRaw(texture.texture, corners, uv, tint, layer);
+	}
+
+	fn pushQuadRaw(&mut self, texture: TextureId, corners: [Vec3; 4], uv: Vec4, tint: Vec4, layer: f32) {
+		if let Some(frustum) = &self.cullFrustum && !frustum.intersectsAabb(&Aabb::fromPoints(&corners).unwrap()) {
+			self.culled += 1;
+			return;
+		}
+		let uvs = [vec2(uv.x, uv.w), vec2(uv.z, uv.w), vec2(uv.z, uv.y), vec2(uv.x, uv.y)];
+		let mut vertices = [0.0; FLOATS * 4];
+		for (i, (pos, uv)) in corners.iter().zip(uvs.iter()).enumerate() {
+			vertices[i * FLOATS..(i + 1) * FLOATS].copy_from_slice(&[
+				pos.x, pos.y, pos.z,
+				uv.x, uv.y,
+				tint.x, tint.y, tint.z, tint.w,
+			]);
+		}
+		self.quads.push(Quad {
+			texture,
+			layer,
+			vertices,
+		});
+	}
+
+	pub fn drawFlush(&mut self, pvMatrix: &Mat4) {
+		profileScope!("SpriteBatch::drawFlush");
+		self.lastDrawCalls = 0;
+		self.lastQuadCount = self.quads.len();
+		self.lastCulled = self.culled;
+		self.culled = 0;
+		if self.quads.is_empty() {
+			return;
+		}
+
+		// Stable sort keeps submission order inside a layer so alpha blends in order, consecutive quads
+		// sharing a texture are merged. Unordered batches also group a layer's textures by first use
+		let mut textures: Vec<TextureId> = Vec::new();
+		let mut keyed: Vec<(f32, usize, usize)> = Vec::with_capacity(self.quads.len());
+		for (i, quad) in self.quads.iter().enumerate() {
+			let textureIndex = match textures.iter().position(|t| *t == quad.texture) {
+				Some(index) => index,
+				None => {
+					textures.push(quad.texture);
+					textures.len() - 1
+				}
+			};
+			keyed.push((quad.layer, textureIndex, i));
+		}
+		if self.unordered {
+			keyed.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+		} else {
+			keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+		}
+
+		self.vertices.clear();
+		self.indices.clear();
+		for (n, (_, _, i)) in keyed.iter().enumerate() {
+			self.vertices.extend_from_slice(&self.quads[*i].vertices);
+			let base = (n * 4) as u32;
+			self.indices.extend_from_slice(&[base, base + 1, base + 2, base + 2, base + 3, base]);
+		}
+
+		self.shader.bind();
+		self.shader.setMatrix4f("u_pvm", pvMatrix);
+		self.shader.setUniform1i("u_texture", 0);
+
+		self.context.enable(BLEND);
+		self.context.blendFunc(SRC_ALPHA, ONE_MINUS_SRC_ALPHA);
+
+		self.buffer.bind();
+		self.buffer.upload(&self.vertices);
+		self.buffer.uploadIndices(&self.indices);
+
+		// One draw call per run of quads sharing a texture
+		let mut runStart = 0;
+		while runStart < keyed.len() {
+			let textureIndex = keyed[runStart].1;
+			let mut runEnd = runStart + 1;
+			while runEnd < keyed.len() && keyed[runEnd].1 == textureIndex {
+				runEnd += 1;
+			}
+
+			self.context.bindTexture(0, Some(textures[textureIndex]));
+			let count = ((runEnd - runStart) * 6) as i32;
+			let offset = (runStart * 6 * size_of::<u32>()) as i32;
+			self.context.drawElements(TRIANGLES, count, offset);
+			self.lastDrawCalls += 1;
+			runStart = runEnd;
+		}
+
+		self.buffer.unbind();
+		self.context.bindTexture(0, None);
+		self.context.disable(BLEND);
+
+		self.quads.clear();
+	}
+
+	pub fn destroy(&mut self) {
+		if self.destroyed {
+			return;
+		}
+		info!("Destroying sprite batch");
+		self.shader.delete();
+		self.buffer.destroy();
+		self.destroyed = true;
+	}
+}
+
+impl Drop for SpriteBatch {
+	fn drop(&mut self) {
+		self.destroy();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::rc::Rc;
+	use glam::{vec2, Mat4};
+	use glow::{BLEND, TRIANGLES};
+	use crate::render::{RecordingContext, RenderCommand, Sprite, SpriteBatch, Texture};
+
+	fn drawCommands(context: &RecordingContext) -> Vec<RenderCommand> {
+		context.commands().into_iter().filter(|c| matches!(c, RenderCommand::DrawElements { .. } | RenderCommand::BindTexture { texture: Some(_), .. })).collect()
+	}
+
+	fn pushAll(batch: &mut SpriteBatch, textures: &[&Texture]) {
+		for (i, texture) in textures.iter().enumerate() {
+			batch.pushSprite(texture, &Sprite {
+				pos: vec2(i as f32, 0.0),
+				..Sprite::default()
+			});
+		}
+	}
+
+	#[test]
+	fn keepsSubmissionOrderInALayer() {
+		let context = Rc::new(RecordingContext::new());
+		let mut batch = SpriteBatch::new(context.clone(), 16).unwrap();
+		let first = Texture::white(context.clone()).unwrap();
+		let second = Texture::white(context.clone()).unwrap();
+		context.clear();
+
+		// Only consecutive quads with the same texture share a draw
+		pushAll(&mut batch, &[&first, &first, &second, &first]);
+		batch.drawFlush(&Mat4::IDENTITY);
+
+		assert_eq!(batch.lastDrawCalls, 3);
+		assert_eq!(drawCommands(&context), [
+			RenderCommand::BindTexture { unit: 0, texture: Some(first.texture) },
+			RenderCommand::DrawElements { mode: TRIANGLES, count: 12, offset: 0 },
+			RenderCommand::BindTexture { unit: 0, texture: Some(second.texture) },
+			RenderCommand::DrawElements { mode: TRIANGLES, count: 6, offset: 48 },
+			RenderCommand::BindTexture { unit: 0, texture: Some(first.texture) },
+			RenderCommand::DrawElements { mode: TRIANGLES, count: 6, offset: 72 },
+		]);
+		let commands = context.commands();
+		assert!(matches!(commands.first(), Some(RenderCommand::UseProgram(Some(_)))));
+		assert_eq!(commands.last(), Some(&RenderCommand::Disable(BLEND)));
+	}
+
+	#[test]
+	fn unorderedGroupsTexturesInALayer() {
+		let context = Rc::new(RecordingContext::new());
+		let mut batch = SpriteBatch::new(context.clone(), 16).unwrap();
+		batch.unordered = true;
+		let first = Texture::white(context.clone()).unwrap();
+		let second = Texture::white(context.clone()).unwrap();
+		context.clear();
+
+		// Grouped by texture in first use order
+		pushAll(&mut batch, &[&first, &second, &first]);
+		batch.drawFlush(&Mat4::IDENTITY);
+
+		assert_eq!(batch.lastDrawCalls, 2);
+		assert_eq!(drawCommands(&context), [
+			RenderCommand::BindTexture { unit: 0, texture: Some(first.texture) },
+			RenderCommand::DrawElements { mode: TRIANGLES, count: 12, offset: 0 },
+			RenderCommand::BindTexture { unit: 0, texture: Some(second.texture) },
+			RenderCommand::DrawElements { mode: TRIANGLES, count: 6, offset: 48 },
+		]);
+	}
+}
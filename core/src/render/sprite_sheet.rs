@@ -0,0 +1,413 @@
+#![allow(non_snake_case)]
+
+use std::collections::HashMap;
+use glam::Vec4;
+use serde_json::Value;
+use crate::render::{AtlasRect, TextureAtlas};
+
+#[derive(Clone)]
+pub struct SpriteFrame {
+	pub name: String,
+	pub rect: AtlasRect,
+	// Seconds
+	pub duration: f32,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[allow(dead_code)]
+pub enum AnimationMode {
+	Once,
+	Loop,
+	PingPong,
+}
+
+#[derive(Clone)]
+pub struct Animation {
+	// Indices into `SpriteSheet::frames`
+	pub frames: Vec<usize>,
+	pub mode: AnimationMode,
+}
+
+pub struct SpriteSheet {
+	pub width: u32,
+	pub height: u32,
+	pub frames: Vec<SpriteFrame>,
+	pub animations: HashMap<String, Animation>,
+}
+
+#[allow(dead_code)]
+impl SpriteSheet {
+	// Frames are read left to right, top to bottom
+	pub fn fromGrid(width: u32, height: u32, frameWidth: u32, frameHeight: u32, frameDuration: f32) -> Result<Self, String> {
+		if frameWidth == 0 || frameHeight == 0 {
+			return Err(format!("Invalid sprite sheet frame size {}x{}", frameWidth, frameHeight));
+		}
+		let columns = width / frameWidth;
+		let rows = height / frameHeight;
+		let mut frames = Vec::with_capacity((columns * rows) as usize);
+		for row in 0..rows {
+			for column in 0..columns {
+				frames.push(SpriteFrame {
+					name: format!("{}", row * columns + column),
+					rect: AtlasRect {
+						x: column * frameWidth,
+						y: row * frameHeight,
+						width: frameWidth,
+						height: frameHeight,
+					},
+					duration: frameDuration,
+				});
+			}
+		}
+		Ok(SpriteSheet {
+			width,
+			height,
+			frames,
+			animations: HashMap::new(),
+		})
+	}
+
+	// Frames are the atlas regions in the order they were added
+	pub fn fromAtlas(atlas: &TextureAtlas, frameDuration: f32) -> Self {
+		let frames = atlas.names.iter().map(|name| SpriteFrame {
+			name: name.clone(),
+			rect: atlas.regions[name],
+			duration: frameDuration,
+		}).collect();
+		SpriteSheet {
+			width: atlas.width,
+			height: atlas.height,
+			frames,
+			animations: HashMap::new(),
+		}
+	}
+
+	/*
+	 * Aseprite and TexturePacker JSON exports, both the hash and array `frames` layouts.
+	 * Animations come from Aseprite `meta.frameTags` or a TexturePacker/Pixi `animations` object.
+	 */
+	pub fn fromJson(json: &str) -> Result<Self, String> {
+		let root: Value = serde_json::from_str(json).map_err(|e| format!("Failed to parse sprite sheet: {}", e))?;
+
+		let mut frames = Vec::new();
+		match root.get("frames") {
+			Some(Value::Object(map)) => {
+				for (name, frame) in map {
+					frames.push(parseFrame(name, frame)?);
+				}
+			}
+			Some(Value::Array(array)) => {
+				for frame in array {
+					let name = frame.get("filename").and_then(Value::as_str).unwrap_or("");
+					frames.push(parseFrame(name, frame)?);
+				}
+			}
+			_ => return Err("Sprite sheet is missing `frames`".to_string()),
+		}
+
+		let meta = root.get("meta");
+		let size = meta.and_then(|m| m.get("size"));
+		let width = size.and_then(|s| s.get("w")).and_then(Value::as_u64);
+		let height = size.and_then(|s| s.get("h")).and_then(Value::as_u64);
+		let (width, height) = match (width, height) {
+			(Some(w), Some(h)) => (w as u32, h as u32),
+			// Fall back to the frame bounds
+			_ => frames.iter().fold((0, 0), |(w, h), f| (w.max(f.rect.x + f.rect.width), h.max(f.rect.y + f.rect.height))),
+		};
+
+		let mut animations = HashMap::new();
+		if let Some(Value::Array(tags)) = meta.and_then(|m| m.get("frameTags")) {
+			for tag in tags {
+				let name = tag.get("name").and_then(Value::as_str).ok_or("Frame tag is missing `name`")?;
+				let from = tag.get("from").and_then(Value::as_u64).ok_or("Frame tag is missing `from`")? as usize;
+				let to = tag.get("to").and_then(Value::as_u64).ok_or("Frame tag is missing `to`")? as usize;
+				if from > to || to >= frames.len() {
+					return Err(format!("Frame tag `{}` is out of range", name));
+				}
+				let mut indices: Vec<usize> = (from..=to).collect();
+				let direction = tag.get("direction").and_then(Value::as_str).unwrap_or("forward");
+				if direction == "reverse" || direction == "pingpong_reverse" {
+					indices.reverse();
+				}
+				let mode = if direction.starts_with("pingpong") {
+					AnimationMode::PingPong
+				} else {
+					AnimationMode::Loop
+				};
+				animations.insert(name.to_string(), Animation {
+					frames: indices,
+					mode,
+				});
+			}
+		}
+		if let Some(Value::Object(anims)) = root.get("animations") {
+			for (name, list) in anims {
+				let list = list.as_array().ok_or_else(|| format!("Animation `{}` must be an array", name))?;
+				let mut indices = Vec::with_capacity(list.len());
+				for frameName in list {
+					let frameName = frameName.as_str().unwrap_or("");
+					let index = frames.iter().position(|f| f.name == frameName)
+						.ok_or_else(|| format!("Animation `{}` references unknown frame `{}`", name, frameName))?;
+					indices.push(index);
+				}
+				animations.insert(name.clone(), Animation {
+					frames: indices,
+					mode: AnimationMode::Loop,
+				});
+			}
+		}
+
+		Ok(SpriteSheet {
+			width,
+			height,
+			frames,
+			animations,
+		})
+	}
+
+	pub fn findFrame(&self, name: &str) -> Option<usize> {
+		self.frames.iter().position(|f| f.name == name)
+	}
+
+	pub fn getUv(&self, frame: usize) -> Option<Vec4> {
+		self.frames.get(frame).map(|f| f.rect.uv(self.width, self.height))
+	}
+
+	// Every frame in order
+	pub fn allFrames(&self, mode: AnimationMode) -> Animation {
+		Animation {
+			frames: (0..self.frames.len()).collect(),
+			mode,
+		}
+	}
+}
+
+fn parseFrame(name: &str, value: &Value) -> Result<SpriteFrame, String> {
+	let frame = value.get("frame").ok_or_else(|| format!("Frame `{}` is missing `frame`", name))?;
+	// TexturePacker stores these turned 90 degrees, they'd draw sideways
+	if value.get("rotated").and_then(Value::as_bool).unwrap_or(false) {
+		return Err(format!("Frame `{}` is rotated, export without rotation", name));
+	}
+	let get = |key: &str| -> Result<u32, String> {
+		frame.get(key).and_then(Value::as_u64).map(|v| v as u32).ok_or_else(|| format!("Frame `{}` is missing `{}`", name, key))
+	};
+	Ok(SpriteFrame {
+		name: name.to_string(),
+		rect: AtlasRect {
+			x: get("x")?,
+			y: get("y")?,
+			width: get("w")?,
+			height: get("h")?,
+		},
+		// Aseprite stores milliseconds, TexturePacker has no per-frame timing
+		duration: value.get("duration").and_then(Value::as_f64).unwrap_or(100.0) as f32 / 1000.0,
+	})
+}
+
+pub struct AnimationPlayer {
+	// Position in `Animation::frames`
+	pub index: usize,
+	pub time: f32,
+	pub speed: f32,
+	pub playing: bool,
+	pub finished: bool,
+	forward: bool,
+}
+
+impl Default for AnimationPlayer {
+	fn default() -> AnimationPlayer {
+		AnimationPlayer {
+			index: 0,
+			time: 0.0,
+			speed: 1.0,
+			playing: true,
+			finished: false,
+			forward: true,
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl AnimationPlayer {
+	pub fn restart(&mut self) {
+		self.index = 0;
+		self.time = 0.0;
+		self.finished = false;
+		self.forward = true;
+		self.playing = true;
+	}
+
+	pub fn update(&mut self, sheet: &SpriteSheet, animation: &Animation, dt: f32) {
+		if !self.playing || self.finished || animation.frames.is_empty() {
+			return;
+		}
+		self.index = self.index.min(animation.frames.len() - 1);
+		self.time += dt * self.speed;
+
+		loop {
+			let duration = sheet.frames[animation.frames[self.index]].duration;
+			if duration <= 0.0 || self.time < duration {
+				break;
+			}
+			self.time -= duration;
+			self.step(animation);
+			if self.finished {
+				break;
+			}
+		}
+	}
+
+	fn step(&mut self, animation: &Animation) {
+		let last = animation.frames.len() - 1;
+		match animation.mode {
+			AnimationMode::Once => {
+				if self.index == last {
+					self.finished = true;
+					self.time = 0.0;
+				} else {
+					self.index += 1;
+				}
+			}
+			AnimationMode::Loop => {
+				self.index = if self.index == last { 0 } else { self.index + 1 };
+			}
+			AnimationMode::PingPong => {
+				if last == 0 {
+					return;
+				}
+				if self.forward && self.index == last {
+					self.forward = false;
+				} else if !self.forward && self.index == 0 {
+					self.forward = true;
+				}
+				if self.forward {
+					self.index += 1;
+				} else {
+					self.index -= 1;
+				}
+			}
+		}
+	}
+
+	// Index into `SpriteSheet::frames`
+	pub fn frame(&self, animation: &Animation) -> usize {
+		animation.frames[self.index.min(animation.frames.len() - 1)]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use glam::Vec4;
+	use crate::render::{AnimationMode, AnimationPlayer, AtlasRect, SpriteSheet};
+
+	// Plays `steps` frame lengths and returns the frame after each
+	fn play(mode: AnimationMode, frames: usize, steps: usize) -> Vec<usize> {
+		let sheet = SpriteSheet::fromGrid(frames as u32 * 8, 8, 8, 8, 0.25).unwrap();
+		let animation = sheet.allFrames(mode);
+		let mut player = AnimationPlayer::default();
+		(0..steps).map(|_| {
+			player.update(&sheet, &animation, 0.25);
+			player.frame(&animation)
+		}).collect()
+	}
+
+	#[test]
+	fn gridFramesGoLeftToRightTopToBottom() {
+		let sheet = SpriteSheet::fromGrid(24, 16, 8, 8, 0.1).unwrap();
+		assert_eq!(sheet.frames.len(), 6);
+		assert_eq!((sheet.frames[4].rect.x, sheet.frames[4].rect.y), (8, 8));
+		assert_eq!(sheet.findFrame("5"), Some(5));
+		assert!(SpriteSheet::fromGrid(24, 16, 0, 8, 0.1).is_err());
+		assert!(SpriteSheet::fromGrid(24, 16, 8, 0, 0.1).is_err());
+	}
+
+	// Aseprite hash layout, frames are kept in file order
+	const ASEPRITE: &str = r#"{
+		"frames": {
+			"walk 1": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "duration": 100 },
+			"walk 0": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "duration": 150 },
+			"walk 2": { "frame": { "x": 32, "y": 0, "w": 16, "h": 16 }, "duration": 100 }
+		},
+		"meta": {
+			"size": { "w": 64, "h": 32 },
+			"frameTags": [
+				{ "name": "forward", "from": 0, "to": 2, "direction": "forward" },
+				{ "name": "reverse", "from": 0, "to": 2, "direction": "reverse" },
+				{ "name": "pingpong", "from": 1, "to": 2, "direction": "pingpong" },
+				{ "name": "pingpongReverse", "from": 0, "to": 1, "direction": "pingpong_reverse" }
+			]
+		}
+	}"#;
+
+	// TexturePacker array layout with Pixi animations and no `meta.size`
+	const TEXTURE_PACKER: &str = r#"{
+		"frames": [
+			{ "filename": "idle", "frame": { "x": 0, "y": 0, "w": 10, "h": 20 }, "rotated": false },
+			{ "filename": "jump", "frame": { "x": 10, "y": 4, "w": 12, "h": 30 } }
+		],
+		"animations": { "hop": ["idle", "jump", "idle"] }
+	}"#;
+
+	#[test]
+	fn loadsAsepriteHash() {
+		let sheet = SpriteSheet::fromJson(ASEPRITE).unwrap();
+		assert_eq!((sheet.width, sheet.height), (64, 32));
+		let names: Vec<&str> = sheet.frames.iter().map(|f| f.name.as_str()).collect();
+		assert_eq!(names, ["walk 1", "walk 0", "walk 2"]);
+		assert_eq!(sheet.frames[1].rect, AtlasRect { x: 0, y: 0, width: 16, height: 16 });
+		assert!((sheet.frames[1].duration - 0.15).abs() < 1e-6);
+		assert_eq!(sheet.getUv(2), Some(Vec4::new(0.5, 0.0, 0.75, 0.5)));
+		assert_eq!(sheet.getUv(3), None);
+
+		let tag = |name: &str| {
+			let animation = &sheet.animations[name];
+			(animation.frames.clone(), animation.mode)
+		};
+		assert_eq!(tag("forward"), (vec![0, 1, 2], AnimationMode::Loop));
+		assert_eq!(tag("reverse"), (vec![2, 1, 0], AnimationMode::Loop));
+		assert_eq!(tag("pingpong"), (vec![1, 2], AnimationMode::PingPong));
+		assert_eq!(tag("pingpongReverse"), (vec![1, 0], AnimationMode::PingPong));
+	}
+
+	#[test]
+	fn loadsTexturePackerArray() {
+		let sheet = SpriteSheet::fromJson(TEXTURE_PACKER).unwrap();
+		// No `meta.size`, so the frame bounds
+		assert_eq!((sheet.width, sheet.height), (22, 34));
+		assert_eq!(sheet.findFrame("jump"), Some(1));
+		assert_eq!(sheet.frames[1].rect, AtlasRect { x: 10, y: 4, width: 12, height: 30 });
+		assert_eq!(sheet.animations["hop"].frames, [0, 1, 0]);
+	}
+
+	#[test]
+	fn rejectsBadSheets() {
+		let outOfRange = ASEPRITE.replace(r#""from": 1, "to": 2"#, r#""from": 1, "to": 3"#);
+		assert_eq!(SpriteSheet::fromJson(&outOfRange).err().unwrap(), "Frame tag `pingpong` is out of range");
+		let unknownFrame = TEXTURE_PACKER.replace(r#""jump", "idle""#, r#""fall", "idle""#);
+		assert_eq!(SpriteSheet::fromJson(&unknownFrame).err().unwrap(), "Animation `hop` references unknown frame `fall`");
+		let rotated = TEXTURE_PACKER.replace(r#""rotated": false"#, r#""rotated": true"#);
+		assert!(SpriteSheet::fromJson(&rotated).is_err());
+		assert!(SpriteSheet::fromJson(r#"{ "meta": {} }"#).is_err());
+	}
+
+	#[test]
+	fn playbackModes() {
+		assert_eq!(play(AnimationMode::Loop, 3, 5), [1, 2, 0, 1, 2]);
+		assert_eq!(play(AnimationMode::Once, 3, 5), [1, 2, 2, 2, 2]);
+		assert_eq!(play(AnimationMode::PingPong, 3, 6), [1, 2, 1, 0, 1, 2]);
+		assert_eq!(play(AnimationMode::PingPong, 1, 3), [0, 0, 0]);
+	}
+
+	#[test]
+	fn largeStepsSkipFrames() {
+		let sheet = SpriteSheet::fromGrid(32, 8, 8, 8, 0.25).unwrap();
+		let animation = sheet.allFrames(AnimationMode::Once);
+		let mut player = AnimationPlayer::default();
+		player.update(&sheet, &animation, 0.6);
+		assert_eq!(player.frame(&animation), 2);
+		assert!((player.time - 0.1).abs() < 1e-5);
+		player.update(&sheet, &animation, 10.0);
+		assert_eq!(player.frame(&animation), 3);
+		assert!(player.finished);
+	}
+}
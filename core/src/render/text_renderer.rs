@@ -0,0 +1,232 @@
+#![allow(non_snake_case)]
+
+use std::rc::Rc;
+use glam::{vec2, Mat4, Vec2, Vec3, Vec4};
+use log::info;
+use crate::geometry::Frustum;
+use crate::render::{Font, RenderContext, SpriteBatch};
+
+#[derive(Copy, Clone, PartialEq)]
+#[allow(dead_code)]
+pub enum TextAlign {
+	Left,
+	Center,
+	Right,
+}
+
+#[derive(Copy, Clone)]
+pub struct TextStyle {
+	pub color: Vec4,
+	// Multiplier on the font's pixel size
+	pub scale: f32,
+	pub align: TextAlign,
+	// Wrap at word boundaries, in scaled units
+	pub maxWidth: Option<f32>,
+	pub lineSpacing: f32,
+	pub layer: f32,
+}
+
+impl Default for TextStyle {
+	fn default() -> TextStyle {
+		TextStyle {
+			color: Vec4::ONE,
+			scale: 1.0,
+			align: TextAlign::Left,
+			maxWidth: None,
+			lineSpacing: 1.0,
+			layer: 0.0,
+		}
+	}
+}
+
+#[derive(Copy, Clone)]
+pub struct PositionedGlyph {
+	pub ch: char,
+	// Bottom-left corner
+	pub pos: Vec2,
+	pub size: Vec2,
+	pub uv: Vec4,
+}
+
+pub struct TextLayout {
+	pub glyphs: Vec<PositionedGlyph>,
+	pub size: Vec2,
+}
+
+/*
+ * Lays out UTF-8 text with kerning, word wrapping and alignment.
+ * Origin is the top-left of the block, y up, so lines go towards -y.
+ */
+pub fn layoutText(font: &mut Font, text: &str, style: &TextStyle) -> TextLayout {
+	let scale = style.scale;
+	let maxWidth = style.maxWidth.map(|w| w / scale);
+
+	let mut lines: Vec<Vec<char>> = Vec::new();
+	for paragraph in text.split('\n') {
+		let mut line: Vec<char> = Vec::new();
+		let mut lineWidth = 0.0;
+		for token in splitTokens(paragraph) {
+			let isSpace = token[0].is_whitespace();
+			let kern = line.last().map(|l| font.kern(*l, token[0])).unwrap_or(0.0);
+			let tokenWidth = measure(font, &token);
+
+			if let Some(max) = maxWidth {
+				if !line.is_empty() && !isSpace && lineWidth + kern + tokenWidth > max {
+					trimEnd(&mut line);
+					lines.push(std::mem::take(&mut line));
+					lineWidth = 0.0;
+				}
+				if line.is_empty() && isSpace {
+					continue;
+				}
+				// Words longer than a line are broken per character
+				if line.is_empty() && tokenWidth > max {
+					for ch in token {
+						let advance = font.glyph(ch).advance;
+						let kern = line.last().map(|l| font.kern(*l, ch)).unwrap_or(0.0);
+						if !line.is_empty() && lineWidth + kern + advance > max {
+							lines.push(std::mem::take(&mut line));
+							lineWidth = 0.0;
+						}
+						lineWidth += kern + advance;
+						line.push(ch);
+					}
+					continue;
+				}
+			}
+
+			lineWidth += kern + tokenWidth;
+			line.extend(token);
+		}
+		lines.push(line);
+	}
+
+	let lineWidths: Vec<f32> = lines.iter().map(|l| measure(font, l)).collect();
+	let blockWidth = maxWidth.unwrap_or_else(|| lineWidths.iter().cloned().fold(0.0, f32::max));
+	let lineHeight = font.lineHeight * style.lineSpacing;
+
+	let mut glyphs = Vec::new();
+	for (i, line) in lines.iter().enumerate() {
+		let mut x = match style.align {
+			TextAlign::Left => 0.0,
+			TextAlign::Center => (blockWidth - lineWidths[i]) * 0.5,
+			TextAlign::Right => blockWidth - lineWidths[i],
+		};
+		let baseline = -(font.ascent + i as f32 * lineHeight);
+		let mut prev: Option<char> = None;
+		for ch in line {
+			if let Some(prev) = prev {
+				x += font.kern(prev, *ch);
+			}
+			let glyph = font.glyph(*ch);
+			if glyph.rect.is_some() {
+				glyphs.push(PositionedGlyph {
+					ch: *ch,
+					pos: vec2(x + glyph.offset.x, baseline + glyph.offset.y) * scale,
+					size: glyph.size * scale,
+					uv: font.glyphUv(&glyph),
+				});
+			}
+			x += glyph.advance;
+			prev = Some(*ch);
+		}
+	}
+
+	let height = if lines.is_empty() { 0.0 } else { font.ascent - font.descent + (lines.len() - 1) as f32 * lineHeight };
+	TextLayout {
+		glyphs,
+		size: vec2(blockWidth, height) * scale,
+	}
+}
+
+// Runs of whitespace and non-whitespace
+fn splitTokens(text: &str) -> Vec<Vec<char>> {
+	let mut tokens: Vec<Vec<char>> = Vec::new();
+	for ch in text.chars() {
+		match tokens.last_mut() {
+			Some(token) if token[0].is_whitespace() == ch.is_whitespace() => token.push(ch),
+			_ => tokens.push(vec![ch]),
+		}
+	}
+	tokens
+}
+
+fn measure(font: &mut Font, chars: &[char]) -> f32 {
+	let mut width = 0.0;
+	let mut prev: Option<char> = None;
+	for ch in chars {
+		if let Some(prev) = prev {
+			width += font.kern(prev, *ch);
+		}
+		width += font.glyph(*ch).advance;
+		prev = Some(*ch);
+	}
+	width
+}
+
+fn trimEnd(line: &mut Vec<char>) {
+	while line.last().is_some_and(|c| c.is_whitespace()) {
+		line.pop();
+	}
+}
+
+/*
+ * Draws laid out glyphs as sprite batch quads. Use one renderer per space (screen or world)
+ * as everything pushed is flushed with the same matrix.
+ */
+pub struct TextRenderer {
+	batch: SpriteBatch,
+	destroyed: bool,
+}
+
+#[allow(dead_code)]
+impl TextRenderer {
+	pub fn new(context: Rc<dyn RenderContext>, capacity: usize) -> Result<Self, String> {
+		Ok(TextRenderer {
+			batch: SpriteBatch::new(context, capacity)?,
+			destroyed: false,
+		})
+	}
+
+	// `pos` is the top-left of the text block, 1 unit per pixel at scale 1
+	pub fn pushText(&mut self, font: &mut Font, text: &str, pos: Vec2, style: &TextStyle) -> Vec2 {
+		self.pushText3D(font, text, &Mat4::from_translation(pos.extend(0.0)), style)
+	}
+
+	// Text is laid out on the XY plane of `transform`
+	pub fn pushText3D(&mut self, font: &mut Font, text: &str, transform: &Mat4, style: &TextStyle) -> Vec2 {
+		let layout = layoutText(font, text, style);
+		for glyph in &layout.glyphs {
+			let corner = |x: f32, y: f32| -> Vec3 {
+				transform.transform_point3((glyph.pos + glyph.size * vec2(x, y)).extend(style.layer))
+			};
+			let corners = [corner(0.0, 0.0), corner(1.0, 0.0), corner(1.0, 1.0), corner(0.0, 1.0)];
+			self.batch.pushQuad(&font.texture, corners, glyph.uv, style.color, style.layer);
+		}
+		layout.size
+	}
+
+	// Glyph quads outside are skipped when pushed
+	pub fn setCullFrustum(&mut self, frustum: Option<Frustum>) {
+		self.batch.cullFrustum = frustum;
+	}
+
+	pub fn drawFlush(&mut self, pvMatrix: &Mat4) {
+		self.batch.drawFlush(pvMatrix);
+	}
+
+	pub fn destroy(&mut self) {
+		if self.destroyed {
+			return;
+		}
+		info!("Destroying text renderer");
+		self.batch.destroy();
+		self.destroyed = true;
+	}
+}
+
+impl Drop for TextRenderer {
+	fn drop(&mut self) {
+		self.destroy();
+	}
+}
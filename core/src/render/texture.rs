@@ -0,0 +1,64 @@
+#![allow(non_snake_case)]
+
+use std::rc::Rc;
+use crate::render::{RenderContext, TextureId};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[allow(dead_code)]
+pub enum TextureFilter {
+	Nearest,
+	Linear,
+}
+
+pub struct Texture {
+	context: Rc<dyn RenderContext>,
+	pub texture: TextureId,
+	pub width: u32,
+	pub height: u32,
+	destroyed: bool,
+}
+
+#[allow(dead_code)]
+impl Texture {
+	pub fn fromRgba(context: Rc<dyn RenderContext>, width: u32, height: u32, pixels: &[u8], filter: TextureFilter) -> Result<Self, String> {
+		if pixels.len() != (width * height * 4) as usize {
+			return Err(format!("Texture data size mismatch: expected {} bytes, got {}", width * height * 4, pixels.len()));
+		}
+
+		let texture = context.createTexture(width, height, pixels, filter)?;
+		Ok(Texture {
+			context,
+			texture,
+			width,
+			height,
+			destroyed: false,
+		})
+	}
+
+	pub fn white(context: Rc<dyn RenderContext>) -> Result<Self, String> {
+		Self::fromRgba(context, 1, 1, &[255, 255, 255, 255], TextureFilter::Nearest)
+	}
+
+	// Replace a region of the texture, `pixels` is tightly packed RGBA
+	pub fn update(&self, x: u32, y: u32, width: u32, height: u32, pixels: &[u8]) {
+		self.context.updateTexture(self.texture, x, y, width, height, pixels);
+	}
+
+	pub fn bind(&self, unit: u32) {
+		self.context.bindTexture(unit, Some(self.texture));
+	}
+
+	pub fn destroy(&mut self) {
+		if self.destroyed {
+			return;
+		}
+		self.context.deleteTexture(self.texture);
+		self.destroyed = true;
+	}
+}
+
+impl Drop for Texture {
+	fn drop(&mut self) {
+		self.destroy();
+	}
+}
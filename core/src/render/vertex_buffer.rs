@@ -0,0 +1,132 @@
+#![allow(non_snake_case)]
+
+use std::rc::Rc;
+use glow::{ARRAY_BUFFER, DYNAMIC_DRAW, ELEMENT_ARRAY_BUFFER};
+use crate::render::{BufferId, RenderContext, RenderObject, Shader, VertexArrayId};
+
+/*
+ * Interleaved float attribute, `size` is the number of floats (1-4)
+ */
+pub struct VertexAttrib {
+	pub name: &'static str,
+	pub size: i32,
+}
+
+impl VertexAttrib {
+	pub const fn new(name: &'static str, size: i32) -> Self {
+		VertexAttrib {
+			name,
+			size,
+		}
+	}
+}
+
+/*
+ * VAO + VBO (+ optional EBO) pair shared by the batch renderers
+ */
+pub struct VertexBuffer {
+	context: Rc<dyn RenderContext>,
+	vao: VertexArrayId,
+	vbo: BufferId,
+	ebo: Option<BufferId>,
+	pub floatsPerVertex: usize,
+	destroyed: bool,
+}
+
+const FLOAT_SIZE: usize = size_of::<f32>();
+
+#[allow(dead_code)]
+impl VertexBuffer {
+	pub fn new(context: Rc<dyn RenderContext>, shader: &Shader, attribs: &[VertexAttrib], indexed: bool) -> Result<Self, String> {
+		let vao = context.createVertexArray()?;
+		let vbo = context.createBuffer()?;
+		let ebo = if indexed {
+			Some(context.createBuffer()?)
+		} else {
+			None
+		};
+
+		context.bindVertexArray(Some(vao));
+		context.bindBuffer(ARRAY_BUFFER, Some(vbo));
+		if let Some(ebo) = ebo {
+			// Element buffer binding is stored in the VAO
+			context.bindBuffer(ELEMENT_ARRAY_BUFFER, Some(ebo));
+		}
+
+		let floatsPerVertex: usize = attribs.iter().map(|a| a.size as usize).sum();
+		let stride = (floatsPerVertex * FLOAT_SIZE) as i32;
+		let mut offset: usize = 0;
+		for attrib in attribs {
+			let loc = shader.getAttribLocation(attrib.name)
+				.ok_or_else(|| format!("Failed to find attribute: {}", attrib.name))?;
+			context.vertexAttribPointer(loc, attrib.size, stride, offset as i32);
+			context.enableVertexAttribArray(loc);
+			offset += attrib.size as usize * FLOAT_SIZE;
+		}
+
+		context.bindVertexArray(None);
+		context.bindBuffer(ARRAY_BUFFER, None);
+		if ebo.is_some() {
+			context.bindBuffer(ELEMENT_ARRAY_BUFFER, None);
+		}
+
+		Ok(VertexBuffer {
+			context,
+			vao,
+			vbo,
+			ebo,
+			floatsPerVertex,
+			destroyed: false,
+		})
+	}
+
+	// Names the VAO and buffers after `label` for GL debug messages and capture tools
+	pub fn setLabel(&self, label: &str) {
+		self.context.setLabel(RenderObject::VertexArray(self.vao), &format!("{} VAO", label));
+		self.context.setLabel(RenderObject::Buffer(self.vbo), &format!("{} VBO", label));
+		if let Some(ebo) = self.ebo {
+			self.context.setLabel(RenderObject::Buffer(ebo), &format!("{} EBO", label));
+		}
+	}
+
+	pub fn bind(&self) {
+		self.context.bindVertexArray(Some(self.vao));
+		self.context.bindBuffer(ARRAY_BUFFER, Some(self.vbo));
+	}
+
+	pub fn unbind(&self) {
+		self.context.bindBuffer(ARRAY_BUFFER, None);
+		self.context.bindVertexArray(None);
+	}
+
+	// Buffer must be bound
+	pub fn upload(&self, vertices: &[f32]) {
+		self.context.bufferData(ARRAY_BUFFER, bytemuck::cast_slice(vertices), DYNAMIC_DRAW);
+	}
+
+	// Buffer must be bound
+	pub fn uploadIndices(&self, indices: &[u32]) {
+		if self.ebo.is_none() {
+			return;
+		}
+		self.context.bufferData(ELEMENT_ARRAY_BUFFER, bytemuck::cast_slice(indices), DYNAMIC_DRAW);
+	}
+
+	pub fn destroy(&mut self) {
+		if self.destroyed {
+			return;
+		}
+		self.context.deleteBuffer(self.vbo);
+		if let Some(ebo) = self.ebo {
+			self.context.deleteBuffer(ebo);
+		}
+		self.context.deleteVertexArray(self.vao);
+		self.destroyed = true;
+	}
+}
+
+impl Drop for VertexBuffer {
+	fn drop(&mut self) {
+		self.destroy();
+	}
+}
@@ -0,0 +1,102 @@
+#![allow(non_snake_case)]
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/*
+ * Small text blobs that outlive the app, files in the working directory on native and localStorage on web
+ */
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[allow(dead_code)]
+pub enum StateFormat {
+	Json,
+	Ron,
+}
+
+pub fn toString<T: Serialize>(value: &T, format: StateFormat) -> Result<String, String> {
+	match format {
+		StateFormat::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+		StateFormat::Ron => ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string()),
+	}
+}
+
+pub fn fromString<T: DeserializeOwned>(text: &str, format: StateFormat) -> Result<T, String> {
+	match format {
+		StateFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+		StateFormat::Ron => ron::from_str(text).map_err(|e| e.to_string()),
+	}
+}
+
+// `key` is a file path on native and a localStorage key on web
+pub fn save<T: Serialize>(key: &str, value: &T, format: StateFormat) -> Result<(), String> {
+	saveString(key, &toString(value, format)?)
+}
+
+// None if nothing has been saved under `key` yet
+pub fn load<T: DeserializeOwned>(key: &str, format: StateFormat) -> Result<Option<T>, String> {
+	match loadString(key)? {
+		Some(text) => fromString(&text, format).map(Some).map_err(|e| format!("Failed to parse {}: {}", key, e)),
+		None => Ok(None),
+	}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn saveString(key: &str, value: &str) -> Result<(), String> {
+	std::fs::write(key, value).map_err(|e| format!("Failed to write {}: {}", key, e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn loadString(key: &str) -> Result<Option<String>, String> {
+	match std::fs::read_to_string(key) {
+		Ok(value) => Ok(Some(value)),
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+		Err(e) => Err(format!("Failed to read {}: {}", key, e)),
+	}
+}
+
+#[cfg(target_arch = "wasm32")]
+fn localStorage() -> Result<web_sys::Storage, String> {
+	web_sys::window()
+		.ok_or("No window")?
+		.local_storage()
+		.map_err(|e| format!("Failed to get localStorage: {:?}", e))?
+		.ok_or_else(|| "localStorage is unavailable".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn saveString(key: &str, value: &str) -> Result<(), String> {
+	localStorage()?.set_item(key, value).map_err(|e| format!("Failed to write {}: {:?}", key, e))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn loadString(key: &str) -> Result<Option<String>, String> {
+	localStorage()?.get_item(key).map_err(|e| format!("Failed to read {}: {:?}", key, e))
+}
+
+// For larger one-off exports (traces, dumps), a file on native and a download on web
+#[cfg(not(target_arch = "wasm32"))]
+pub fn exportFile(name: &str, text: &str) -> Result<(), String> {
+	saveString(name, text)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn exportFile(name: &str, text: &str) -> Result<(), String> {
+	use web_sys::wasm_bindgen::{JsCast, JsValue};
+	let document = web_sys::window().and_then(|w| w.document()).ok_or("No document")?;
+	let parts = web_sys::js_sys::Array::of1(&JsValue::from_str(text));
+	let options = web_sys::BlobPropertyBag::new();
+	options.set_type("application/octet-stream");
+	let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options).map_err(|e| format!("Failed to export {}: {:?}", name, e))?;
+	let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(|e| format!("Failed to export {}: {:?}", name, e))?;
+	// A temporary link the browser downloads from
+	let anchor = document.create_element("a")
+		.map_err(|e| format!("Failed to export {}: {:?}", name, e))?
+		.dyn_into::<web_sys::HtmlAnchorElement>()
+		.map_err(|_| format!("Failed to export {}", name))?;
+	anchor.set_href(&url);
+	anchor.set_download(name);
+	anchor.click();
+	let _ = web_sys::Url::revoke_object_url(&url);
+	Ok(())
+}
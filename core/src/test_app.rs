@@ -1,32 +1,74 @@
 #![allow(non_snake_case)]
 
 use std::rc::Rc;
-use glam::{vec3, Mat4, Vec3};
+use glam::{vec2, vec3, vec4, Mat4, Quat, Vec2, Vec3};
 use glow::*;
-use log::info;
+use log::{info, warn};
+use winit::event::WindowEvent;
 use winit::event_loop::ActiveEventLoop;
-use winit::keyboard::KeyCode;
 use winit::window::Window;
-use winit_input_helper::WinitInputHelper;
-use crate::camera::{Camera, Movement};
-use crate::render::LineRenderer;
+use crate::camera::{orientationFromEuler, Camera, Camera2D, CameraBookmarks, ClipDepth, CameraMode, CameraPath, CameraPose, FollowRig, Movement, PathPlayer, Projection};
+use crate::storage::{self, StateFormat};
+use crate::{debugText, profileScope, profiler};
+use crate::geometry::{Aabb, Plane};
+use crate::gui::Gui;
+use crate::input::{ActionMap, CursorCapture, GamepadEvent, InputFrame, TextInput, TouchInput, VirtualJoystick};
+use crate::render::{Animation, AnimationMode, AnimationPlayer, AtlasBuilder, DebugOverlay, Font, GlowContext, GpuProfiler, Image, LineRenderer, RenderContext, Sprite, SpriteBatch, SpriteSheet, TextAlign, TextRenderer, TextStyle, Texture, TextureFilter};
+use crate::timestep::FixedTimestep;
+use crate::timing::{FrameLimiter, FrameStats};
 
 pub struct TestApp {
 	window: Rc<Window>,
-	gl: Rc<Context>,
+	context: Rc<dyn RenderContext>,
 	camera: Camera,
+	followRig: FollowRig,
+	following: bool,
+	cameraPath: CameraPath,
+	pathPlayer: PathPlayer,
+	bookmarks: CameraBookmarks,
+	actions: ActionMap,
 	lineRenderer: LineRenderer,
+	camera2D: Camera2D,
+	spriteBatch: SpriteBatch,
+	checkerTexture: Texture,
+	whiteTexture: Texture,
+	atlasTexture: Texture,
+	spriteSheet: SpriteSheet,
+	animation: Animation,
+	animationPlayer: AnimationPlayer,
+	font: Font,
+	screenText: TextRenderer,
+	worldText: TextRenderer,
+	debugOverlay: DebugOverlay,
+	gpuProfiler: GpuProfiler,
+	gui: Gui,
+	guiNote: String,
 
-	mouseCaptured: bool,
+	cursor: CursorCapture,
+	touch: TouchInput,
+	textField: TextInput,
+	// Camera at the start of the last tick, rendering interpolates from it
+	previousPose: CameraPose,
+	followTarget: Option<Vec3>,
+	hoverCursor: Option<Vec2>,
+	gamepadName: Option<String>,
+	frameLimiter: FrameLimiter,
 	time: f32,
 }
 
+const BOOKMARKS_KEY: &str = "camera_bookmarks.ron";
+const INPUT_KEY: &str = "input.ron";
+const TRACE_FILE: &str = "trace.json";
+// From the bottom of the window, above the sprites
+const TEXT_FIELD_Y: f32 = 100.0;
+
 fn norm(v: Vec3) -> Vec3 {
 	v.normalize() * 0.5 + 0.5
 }
 
 impl TestApp {
-	pub fn new(window: Rc<Window>, gl: Rc<Context>, (width, height): (u32, u32)) -> Self {
+	// `clipDepth` is what the runner set up with clip control
+	pub fn new(window: Rc<Window>, gl: Rc<Context>, (width, height): (u32, u32), clipDepth: ClipDepth) -> Self {
 		unsafe {
 			gl.viewport(0, 0, width as i32, height as i32); // `window.inner_size()` return (0, 0) on wasm
 			// gl.viewport(0, 0, window.inner_size().width as i32, window.inner_size().height as i32);
@@ -39,19 +81,109 @@ impl TestApp {
 		
 		let camera = Camera {
 			pos: Vec3::new(0.0, 0.0, 5.0),
+			clipDepth,
 			..Camera::default()
 		};
 		
-		let lineRenderer = LineRenderer::new(gl.clone(), 1024).unwrap();
+		// Circles the cube looking at it
+		let mut cameraPath = CameraPath::new(true);
+		for i in 0..=4 {
+			let yaw = i as f32 * 90.0;
+			let pos = vec3(-yaw.to_radians().cos(), 0.3, -yaw.to_radians().sin()) * 6.0;
+			cameraPath.addKey(i as f32 * 2.0, pos, orientationFromEuler(yaw, -(0.3f32).atan().to_degrees(), 0.0));
+		}
+		
+		let bookmarks = CameraBookmarks::load(BOOKMARKS_KEY, StateFormat::Ron).unwrap_or_else(|e| {
+			warn!("{}", e);
+			CameraBookmarks::default()
+		});
+		
+		let mut actions = ActionMap::fromString(include_str!("../../resources/config/input.ron"), StateFormat::Ron).unwrap();
+		match ActionMap::load(INPUT_KEY, StateFormat::Ron) {
+			Ok(Some(overrides)) => actions.merge(overrides),
+			Ok(None) => {}
+			Err(e) => warn!("{}", e),
+		}
+		
+		// Everything drawn per frame goes through the context, only the setup above uses glow directly
+		let context: Rc<dyn RenderContext> = Rc::new(GlowContext::new(gl.clone()));
+		let lineRenderer = LineRenderer::new(context.clone(), 1024).unwrap();
+		let spriteBatch = SpriteBatch::new(context.clone(), 256).unwrap();
+		let checkerTexture = Texture::fromRgba(context.clone(), 2, 2, &[
+			255, 255, 255, 255, 64, 64, 64, 255,
+			64, 64, 64, 255, 255, 255, 255, 255,
+		], TextureFilter::Nearest).unwrap();
+		let whiteTexture = Texture::white(context.clone()).unwrap();
+		
+		// Generated frames, a bar that grows each frame
+		let mut atlasBuilder = AtlasBuilder::default();
+		for frame in 0..4u32 {
+			let mut image = Image::new(8, 8);
+			for y in 0..8 {
+				for x in 0..8 {
+					let i = ((y * 8 + x) * 4) as usize;
+					let lit = x <= frame * 2 + 1;
+					image.pixels[i..i + 4].copy_from_slice(&[if lit { 255 } else { 40 }, (frame * 60) as u8, 80, 255]);
+				}
+			}
+			atlasBuilder.add(&format!("bar{}", frame), image).unwrap();
+		}
+		let (atlasImage, atlas) = atlasBuilder.build().unwrap();
+		let atlasTexture = Texture::fromRgba(context.clone(), atlasImage.width, atlasImage.height, &atlasImage.pixels, TextureFilter::Nearest).unwrap();
+		let spriteSheet = SpriteSheet::fromAtlas(&atlas, 0.2);
+		let animation = spriteSheet.allFrames(AnimationMode::PingPong);
+		
+		let font = Font::fromBytes(context.clone(), include_bytes!("../../resources/fonts/DejaVuSansMono.ttf"), 24.0).unwrap();
+		let screenText = TextRenderer::new(context.clone(), 256).unwrap();
+		let worldText = TextRenderer::new(context.clone(), 256).unwrap();
+		let debugOverlay = DebugOverlay::new(context.clone()).unwrap();
+		let gpuProfiler = GpuProfiler::new(context.clone());
+		let gui = Gui::new(context.clone(), &window).unwrap();
+		// Move on the left, look on the right
+		let mut touch = TouchInput::default();
+		touch.joysticks = vec![VirtualJoystick::new(vec2(0.15, 0.8), 60.0), VirtualJoystick::new(vec2(0.85, 0.8), 60.0)];
+		
+		let previousPose = camera.getPose();
 		
 		TestApp {
 			window,
-			gl,
+			context,
 			camera,
+			followRig: FollowRig::default(),
+			following: false,
+			cameraPath,
+			pathPlayer: PathPlayer::default(),
+			bookmarks,
+			actions,
 			lineRenderer,
+			camera2D: Camera2D {
+				clipDepth,
+				..Camera2D::default()
+			},
+			spriteBatch,
+			checkerTexture,
+			whiteTexture,
+			atlasTexture,
+			spriteSheet,
+			animation,
+			animationPlayer: AnimationPlayer::default(),
+			font,
+			screenText,
+			worldText,
+			debugOverlay,
+			gpuProfiler,
+			gui,
+			guiNote: String::new(),
 			
 			// windowSize: I16Vec2::new(width, height),
-			mouseCaptured: false,
+			cursor: CursorCapture::default(),
+			touch,
+			textField: TextInput::default(),
+			previousPose,
+			followTarget: None,
+			hoverCursor: None,
+			gamepadName: None,
+			frameLimiter: FrameLimiter::default(),
 			time: 0.0,
 		}
 	}
@@ -60,63 +192,240 @@ impl TestApp {
 	pub fn resize(&mut self, width: u32, height: u32) {
 		// Stretches/Shrinks on Arch Linux Wayland, but works fine without it
 		#[cfg(not(target_os = "linux"))]
-		unsafe {
+		{
 			// info!("{} {}", width, height);
-			// self.gl.viewport(0, 0, width as i32, height as i32);
+			// self.context.viewport(0, 0, width as i32, height as i32);
 			let size = self.window.inner_size();
 			info!("{} {}", size.width, size.height);
-			self.gl.viewport(0, 0, size.width as i32, size.height as i32);
+			self.context.viewport(0, 0, size.width as i32, size.height as i32);
 		}
 	}
 
+	// Returns true if the event was consumed by the GUI
+	pub fn windowEvent(&mut self, event: &WindowEvent) -> bool {
+		self.cursor.onWindowEvent(&self.window, event);
+		self.gui.onWindowEvent(&self.window, event)
+	}
+
 	#[allow(unused)]
-	pub fn update(&mut self, dt: f64, input: &WinitInputHelper, eventLoop: &ActiveEventLoop) {
+	pub fn update(&mut self, input: &InputFrame, eventLoop: &ActiveEventLoop) {
+		profileScope!("TestApp::update");
+		let dt = input.dt;
 		self.time += dt as f32;
+		self.previousPose = self.camera.getPose();
+		let size = self.window.inner_size();
+		self.camera.setViewportSize(size.width as f32, size.height as f32);
+		for event in &input.touches {
+			self.touch.onTouchEvent(event);
+		}
+		self.touch.update(dt as f32, vec2(size.width as f32, size.height as f32));
+		
+		// In-app text field, Enter to open and submit, Escape to cancel
+		let typing = self.textField.isFocused();
+		let response = self.textField.update(&input.text);
+		if let Some(text) = response.submitted {
+			if !text.is_empty() {
+				info!("Text: {}", text);
+			}
+			self.textField.blur(&self.window);
+		}
+		if response.cancelled {
+			self.textField.blur(&self.window);
+		}
+		
+		// Keyboard and scroll go to the GUI or text field while it has focus
+		if self.gui.wantsKeyboard() || typing {
+			self.actions.reset();
+		} else {
+			self.actions.update(input);
+		}
+		self.gamepadName = input.gamepads.connected().next().map(|(_, pad)| pad.name.clone());
+		for event in input.gamepads.events() {
+			match event {
+				GamepadEvent::Connected { id, name } => info!("Gamepad {} connected: {}", id, name),
+				GamepadEvent::Disconnected { id } => info!("Gamepad {} disconnected", id),
+			}
+		}
+		let pointer = !self.gui.wantsPointer();
 
 		// Don't allow user to escape loop in web env
 		#[cfg(not(target_arch = "wasm32"))]
-		if input.key_pressed(KeyCode::Escape) {
+		if self.actions.pressed("quit") {
 			eventLoop.exit();
 		}
 
-		if input.key_pressed(KeyCode::Digit1) {
-			self.mouseCaptured = !self.mouseCaptured;
-			info!("mouseCaptured: {}", self.mouseCaptured);
-
-			// Confines to window, setting cursor pos is only allowed in Locked for wayland
-			// #[cfg(not(target_arch = "wasm32"))]
-			// if self.mouseCaptured {
-			// 	self.window.set_cursor_grab(CursorGrabMode::Confined).expect("Failed to confine cursor");
-			// 	self.window.set_cursor_visible(false);
-			// } else {
-			// 	self.window.set_cursor_grab(CursorGrabMode::None).expect("Failed to unconfine cursor");
-			// 	self.window.set_cursor_visible(true);
-			// }
+		self.cursor.update(&self.window);
+		if self.actions.pressed("text_input") {
+			self.textField.focus(&self.window, vec2(10.0, size.height as f32 - TEXT_FIELD_Y), vec2(400.0, 20.0));
+		}
+		if self.actions.pressed("toggle_mouse") {
+			self.cursor.toggle(&self.window);
 		}
 		
-		if input.key_held(KeyCode::KeyW) {
-			self.camera.processMovement(Movement::Forward, dt as f32);
+		if self.actions.pressed("cycle_camera_mode") {
+			let mode = match self.camera.mode {
+				CameraMode::Fly => CameraMode::Orbit,
+				CameraMode::Orbit => CameraMode::Free,
+				CameraMode::Free => CameraMode::Fly,
+			};
+			self.camera.setMode(mode);
+			info!("Camera mode: {:?}", mode);
+		}
+		if self.actions.pressed("cycle_projection") {
+			self.camera.projection = match self.camera.projection {
+				// Reverse-Z is skipped without 0..1 clip depth
+				Projection::Perspective { reverseZ: false, .. } if self.camera.clipDepth == ClipDepth::ZeroToOne => Projection::Perspective { near: 0.1, far: 100.0, reverseZ: true },
+				Projection::Perspective { .. } => Projection::orthographic(6.0),
+				Projection::Orthographic { pixelPerfect: false, .. } => Projection::pixelPerfect(64.0),
+				Projection::Orthographic { pixelPerfect: true, .. } => Projection::default(),
+			};
+			info!("Projection: {:?}", self.camera.projection);
+		}
+		if self.actions.pressed("frame") {
+			self.camera.frameBounds(Vec3::splat(-1.0), Vec3::splat(1.0));
+		}
+		if self.actions.pressed("home") {
+			self.camera.transitionTo(CameraPose { pos: vec3(0.0, 0.0, 5.0), orientation: Quat::IDENTITY }, 1.0);
 		}
-		if input.key_held(KeyCode::KeyS) {
-			self.camera.processMovement(Movement::Backward, dt as f32);
+		if self.actions.pressed("play_path") {
+			if self.pathPlayer.playing {
+				self.pathPlayer.stop();
+			} else {
+				self.following = false;
+				self.pathPlayer.play();
+			}
 		}
-		if input.key_held(KeyCode::KeyA) {
-			self.camera.processMovement(Movement::Left, dt as f32);
+		if self.actions.pressed("follow") {
+			self.following = !self.following;
+			self.followRig.reset();
+			self.pathPlayer.stop();
 		}
-		if input.key_held(KeyCode::KeyD) {
-			self.camera.processMovement(Movement::Right, dt as f32);
+		if self.actions.pressed("bookmark_add") {
+			let name = format!("View {}", self.bookmarks.bookmarks.len() + 1);
+			self.bookmarks.add(&name, self.camera.getState());
+			match self.bookmarks.save(BOOKMARKS_KEY, StateFormat::Ron) {
+				Ok(()) => info!("Saved bookmark {}", name),
+				Err(e) => warn!("{}", e),
+			}
 		}
-		if input.key_held(KeyCode::Space) {
-			self.camera.processMovement(Movement::Up, dt as f32);
+		let bookmark = if self.actions.pressed("bookmark_next") {
+			self.bookmarks.jumpNext()
+		} else if self.actions.pressed("bookmark_previous") {
+			self.bookmarks.jumpPrevious()
+		} else {
+			None
+		};
+		if let Some(bookmark) = bookmark {
+			info!("Bookmark {}", bookmark.name);
+			self.following = false;
+			self.pathPlayer.stop();
+			self.camera.setMode(bookmark.state.mode);
+			self.camera.fov = bookmark.state.fov;
+			self.camera.transitionTo(bookmark.state.pose(), 0.5);
+		}
+		self.camera.motion.sprinting = self.actions.held("sprint");
+		
+		let moveStick = self.touch.joystick(0);
+		let movement = [
+			("move_x", moveStick.x, Movement::Right, Movement::Left),
+			("move_y", 0.0, Movement::Up, Movement::Down),
+			("move_z", moveStick.y, Movement::Forward, Movement::Backward),
+		];
+		for (axis, touchValue, positive, negative) in movement {
+			let value = self.actions.axis(axis);
+			let value = if touchValue.abs() > value.abs() { touchValue } else { value };
+			if value > 0.0 {
+				self.camera.processMovementAxis(positive, value, dt as f32);
+			} else if value < 0.0 {
+				self.camera.processMovementAxis(negative, -value, dt as f32);
+			}
 		}
-		if input.key_held(KeyCode::ShiftLeft) {
-			self.camera.processMovement(Movement::Down, dt as f32);
+		// Stick look at up to 120 degrees per second with the default sensitivity
+		let look = (vec2(self.actions.axis("look_x"), self.actions.axis("look_y")) + self.touch.joystick(1)) * 1200.0 * dt as f32;
+		if look != Vec2::ZERO {
+			self.camera.processMouseMovement(look.x, look.y, true);
 		}
+		self.camera.processRoll(self.actions.axis("roll") * 90.0 * dt as f32);
 		
-		if self.mouseCaptured {
-			// info!("{:?}", input.cursor_diff());
-			self.camera.processMouseScroll(input.scroll_diff().1);
-			self.camera.processMouseMovement(input.cursor_diff().0, -input.cursor_diff().1, true);
+		// The GUI gets the pointer first, dragging a widget shouldn't turn the camera
+		if self.cursor.isCaptured() && pointer {
+			self.camera.processMouseScroll(input.scrollDiff.1);
+			// Raw motion, a locked cursor doesn't move
+			self.camera.processMouseMovement(input.mouseDiff.0, -input.mouseDiff.1, true);
+		} else if self.camera.mode == CameraMode::Orbit && pointer {
+			// Model viewer controls, drag to rotate/pan
+			let (dx, dy) = input.cursorDiff;
+			if self.actions.held("orbit_rotate") {
+				self.camera.processMouseMovement(dx, -dy, true);
+			}
+			if self.actions.held("orbit_pan") {
+				self.camera.orbitPan(dx, dy);
+			}
+			self.camera.processMouseScroll(input.scrollDiff.1);
+		}
+		// Drag to look or rotate, two finger pan in orbit mode, pinch to zoom
+		let gesture = self.touch.gesture();
+		if pointer {
+			if gesture.drag != Vec2::ZERO {
+				self.camera.processMouseMovement(gesture.drag.x, -gesture.drag.y, true);
+			}
+			if self.camera.mode == CameraMode::Orbit && gesture.pan != Vec2::ZERO {
+				self.camera.orbitPan(gesture.pan.x, gesture.pan.y);
+			}
+			if gesture.pinch != 0.0 {
+				self.camera.processMouseScroll(gesture.pinch * 10.0);
+			}
+		}
+		self.camera.update(dt as f32);
+		self.pathPlayer.update(&self.cameraPath, &mut self.camera, dt as f32);
+		if self.following {
+			// A point running around the cube, the arm is pulled in when the cube is in the way
+			let angle = self.time * 0.5;
+			let target = vec3(angle.cos(), -1.0, angle.sin()) * vec3(3.0, 1.0, 3.0);
+			let targetYaw = (angle + std::f32::consts::FRAC_PI_2).to_degrees();
+			let cube = Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0));
+			self.camera.updateFollow(&mut self.followRig, target, targetYaw, dt as f32, |ray, maxDistance| {
+				ray.intersectAabb(&cube).filter(|t| *t <= maxDistance)
+			});
+			self.followTarget = Some(target);
+		} else {
+			self.followTarget = None;
+		}
+		// Taps act as clicks, hover picking happens at render time
+		self.hoverCursor = input.cursor.map(|(x, y)| vec2(x, y));
+		for tap in self.touch.taps() {
+			if let Some((name, p)) = self.pick(*tap) {
+				info!("Tapped {} at {:.2} {:.2} {:.2}", name, p.x, p.y, p.z);
+			}
+		}
+	}
+	
+	// Lines, sprites and text for one rendered frame, `dt` is the real frame time
+	fn pushScene(&mut self, time: f32, dt: f32) {
+		profileScope!("TestApp::pushScene");
+		// Joysticks only show up once the screen has been touched
+		if self.touch.wasUsed() {
+			let viewport = self.touch.viewport();
+			for joystick in &self.touch.joysticks {
+				// Screen space sprites are bottom-left origin
+				let center = joystick.center(viewport);
+				let center = vec2(center.x, viewport.y - center.y);
+				let alpha = if joystick.isActive() { 0.4 } else { 0.2 };
+				self.spriteBatch.pushSprite(&self.whiteTexture, &Sprite {
+					pos: center,
+					size: Vec2::splat(joystick.radius * 2.0),
+					tint: vec4(1.0, 1.0, 1.0, alpha),
+					layer: 10.0,
+					..Sprite::default()
+				});
+				self.spriteBatch.pushSprite(&self.whiteTexture, &Sprite {
+					pos: center + joystick.value * joystick.radius,
+					size: Vec2::splat(joystick.radius * 0.8),
+					tint: vec4(1.0, 1.0, 1.0, alpha + 0.2),
+					layer: 11.0,
+					..Sprite::default()
+				});
+			}
 		}
 		
 		let mut b1 = vec3(-1.0, -1.0, -1.0);
@@ -137,32 +446,32 @@ impl TestApp {
 		let t3c = norm(t3);
 		let t4c = norm(t4);
 		
-		b1 = b1.rotate_x(self.time);
-		b2 = b2.rotate_x(self.time);
-		b3 = b3.rotate_x(self.time);
-		b4 = b4.rotate_x(self.time);
-		t1 = t1.rotate_x(self.time);
-		t2 = t2.rotate_x(self.time);
-		t3 = t3.rotate_x(self.time);
-		t4 = t4.rotate_x(self.time);
-		
-		b1 = b1.rotate_y(self.time);
-		b2 = b2.rotate_y(self.time);
-		b3 = b3.rotate_y(self.time);
-		b4 = b4.rotate_y(self.time);
-		t1 = t1.rotate_y(self.time);
-		t2 = t2.rotate_y(self.time);
-		t3 = t3.rotate_y(self.time);
-		t4 = t4.rotate_y(self.time);
-		
-		// b1 = b1.rotate_z(self.time);
-		// b2 = b2.rotate_z(self.time);
-		// b3 = b3.rotate_z(self.time);
-		// b4 = b4.rotate_z(self.time);
-		// t1 = t1.rotate_z(self.time);
-		// t2 = t2.rotate_z(self.time);
-		// t3 = t3.rotate_z(self.time);
-		// t4 = t4.rotate_z(self.time);
+		b1 = b1.rotate_x(time);
+		b2 = b2.rotate_x(time);
+		b3 = b3.rotate_x(time);
+		b4 = b4.rotate_x(time);
+		t1 = t1.rotate_x(time);
+		t2 = t2.rotate_x(time);
+		t3 = t3.rotate_x(time);
+		t4 = t4.rotate_x(time);
+		
+		b1 = b1.rotate_y(time);
+		b2 = b2.rotate_y(time);
+		b3 = b3.rotate_y(time);
+		b4 = b4.rotate_y(time);
+		t1 = t1.rotate_y(time);
+		t2 = t2.rotate_y(time);
+		t3 = t3.rotate_y(time);
+		t4 = t4.rotate_y(time);
+		
+		// b1 = b1.rotate_z(time);
+		// b2 = b2.rotate_z(time);
+		// b3 = b3.rotate_z(time);
+		// b4 = b4.rotate_z(time);
+		// t1 = t1.rotate_z(time);
+		// t2 = t2.rotate_z(time);
+		// t3 = t3.rotate_z(time);
+		// t4 = t4.rotate_z(time);
 
 		self.lineRenderer.pushLine(b1, b1c, b2, b2c);
 		self.lineRenderer.pushLine(b2, b2c, b3, b3c);
@@ -178,22 +487,206 @@ impl TestApp {
 		self.lineRenderer.pushLine(b2, b2c, t2, t2c);
 		self.lineRenderer.pushLine(b3, b3c, t3, t3c);
 		self.lineRenderer.pushLine(b4, b4c, t4, t4c);
+		
+		for i in 0..3 {
+			self.spriteBatch.pushSprite(&self.checkerTexture, &Sprite {
+				pos: vec2(40.0 + i as f32 * 60.0, 40.0),
+				size: vec2(40.0, 40.0),
+				rotation: time * (i + 1) as f32 * 0.5,
+				tint: vec4(b1c.x, b3c.y, t2c.z, 0.8),
+				layer: i as f32,
+				..Sprite::default()
+			});
+		}
+		
+		self.animationPlayer.update(&self.spriteSheet, &self.animation, dt);
+		if let Some(uv) = self.spriteSheet.getUv(self.animationPlayer.frame(&self.animation)) {
+			self.spriteBatch.pushSprite(&self.atlasTexture, &Sprite {
+				pos: vec2(220.0, 40.0),
+				size: vec2(40.0, 40.0),
+				uv,
+				..Sprite::default()
+			});
+		}
+		
+		let height = self.window.inner_size().height as f32;
+		self.screenText.pushText(&mut self.font, "Glow WASM Workspace\nWASD/Space/Shift to move, Ctrl to sprint, H for home, 1 to capture mouse\n2 to cycle fly/orbit/free (Q/E roll), 3 to cycle projection\nF to frame, P to play path, G to follow\nB to bookmark, [ ] to cycle bookmarks, Enter to type", vec2(10.0, height - 10.0), &TextStyle {
+			scale: 0.75,
+			..TextStyle::default()
+		});
+		if self.textField.isFocused() {
+			let (before, after) = self.textField.text.split_at(self.textField.cursor());
+			let line = format!("> {}{}|{}", before, self.textField.preedit(), after);
+			self.screenText.pushText(&mut self.font, &line, vec2(10.0, TEXT_FIELD_Y), &TextStyle {
+				scale: 0.75,
+				..TextStyle::default()
+			});
+		}
+		let transform = Mat4::from_translation(vec3(0.0, 2.2, 0.0)) * Mat4::from_scale(Vec3::splat(0.02));
+		self.worldText.pushText3D(&mut self.font, "Cube", &(transform * Mat4::from_translation(vec3(-100.0, 0.0, 0.0))), &TextStyle {
+			color: vec4(1.0, 0.8, 0.2, 1.0),
+			align: TextAlign::Center,
+			maxWidth: Some(200.0),
+			..TextStyle::default()
+		});
 	}
 	
-	pub fn render(&mut self) {
-		unsafe {
-			self.gl.clear(COLOR_BUFFER_BIT | DEPTH_BUFFER_BIT);
-			self.gl.clear_color(0.0, 0.0, 0.0, 1.0);
+	// Closest hit on the cube's bounds, then the floor
+	fn pick(&self, screen: Vec2) -> Option<(&'static str, Vec3)> {
+		let ray = self.camera.screenPointToRay(screen);
+		let cube = Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0));
+		let floor = Plane::fromPointNormal(vec3(0.0, -1.5, 0.0), Vec3::Y);
+		let hit = ray.intersectAabb(&cube).map(|t| ("Cube", t)).or_else(|| ray.intersectPlane(&floor).map(|t| ("Floor", t)));
+		hit.map(|(name, t)| (name, ray.at(t)))
+	}
+	
+	// Read by the runner each frame
+	pub fn frameLimiter(&self) -> &FrameLimiter {
+		&self.frameLimiter
+	}
+	
+	pub fn render(&mut self, timestep: &FixedTimestep, stats: &FrameStats) {
+		profileScope!("TestApp::render");
+		// Between the last two ticks, keeps motion smooth when the frame rate doesn't match the tick rate
+		let alpha = timestep.alpha();
+		let time = self.time - (1.0 - alpha) * timestep.dt() as f32;
+		let frameTime = timestep.frameTime() as f32;
+		self.gpuProfiler.beginFrame();
+		
+		let camera = &mut self.camera;
+		let lineRenderer = &mut self.lineRenderer;
+		let spriteBatch = &mut self.spriteBatch;
+		let guiNote = &mut self.guiNote;
+		let limiter = &mut self.frameLimiter;
+		let gpuProfiler = &mut self.gpuProfiler;
+		let mut saveTrace = false;
+		self.gui.run(&self.window, |ctx| {
+			egui::Window::new("Debug").default_pos([10.0, 220.0]).show(ctx, |ui| {
+				ui.add(egui::Slider::new(&mut camera.speed, 0.5..=20.0).text("Speed"));
+				ui.add(egui::Slider::new(&mut camera.sensitivity, 0.01..=1.0).text("Sensitivity"));
+				ui.add(egui::Slider::new(&mut camera.fov, 1.0..=45.0).text("FOV"));
+				ui.add(egui::Slider::new(&mut camera.motion.acceleration, 0.0..=30.0).text("Acceleration"));
+				ui.add(egui::Slider::new(&mut camera.motion.damping, 0.0..=30.0).text("Damping"));
+				ui.add(egui::Slider::new(&mut camera.motion.mouseSmoothing, 0.0..=0.2).text("Mouse smoothing"));
+				ui.checkbox(&mut lineRenderer.enabled, "Lines");
+				ui.checkbox(&mut spriteBatch.enabled, "Sprites");
+				ui.horizontal(|ui| {
+					let mut capped = limiter.maxFps.is_some();
+					let mut maxFps = limiter.maxFps.unwrap_or(60.0);
+					ui.checkbox(&mut capped, "FPS cap");
+					ui.add_enabled(capped, egui::Slider::new(&mut maxFps, 10.0..=240.0));
+					limiter.maxFps = capped.then_some(maxFps);
+				});
+				ui.checkbox(&mut limiter.vsync, "VSync");
+				ui.text_edit_singleline(guiNote);
+				ui.collapsing("GPU passes", |ui| {
+					if !gpuProfiler.isSupported() {
+						ui.label("Timer queries unavailable");
+						return;
+					}
+					ui.checkbox(&mut gpuProfiler.enabled, "Profile");
+					egui::Grid::new("gpu_passes").striped(true).show(ui, |ui| {
+						ui.label("Pass");
+						ui.label("ms");
+						ui.label("avg");
+						ui.end_row();
+						for timing in gpuProfiler.timings() {
+							ui.label(&timing.name);
+							ui.label(format!("{:.3}", timing.time * 1000.0));
+							ui.label(format!("{:.3}", timing.average * 1000.0));
+							ui.end_row();
+						}
+					});
+					ui.label(format!("Total: {:.3} ms", gpuProfiler.total() * 1000.0));
+				});
+				ui.horizontal(|ui| {
+					let mut profiling = profiler::isEnabled();
+					if ui.checkbox(&mut profiling, "CPU profiler").changed() {
+						profiler::setEnabled(profiling);
+					}
+					// CPU spans and GPU passes in one trace, for chrome://tracing or Perfetto
+					saveTrace = ui.button("Save trace").clicked();
+				});
+			});
+		});
+		if saveTrace {
+			match profiler::chromeTrace(self.gpuProfiler.traceEvents()).and_then(|trace| storage::exportFile(TRACE_FILE, &trace)) {
+				Ok(()) => info!("Saved trace to {}", TRACE_FILE),
+				Err(e) => warn!("{}", e),
+			}
+		}
+		
+		if let Some(target) = self.followTarget {
+			let color = vec3(0.2, 1.0, 0.4);
+			self.lineRenderer.pushLine(target, color, target + Vec3::Y, color);
+		}
+		// Cull against the interpolated view that's drawn, the current tick's would pop edges in a frame late
+		let (width, height) = (self.window.inner_size().width as f32, self.window.inner_size().height as f32);
+		let frustum = self.camera.getInterpolatedFrustum(width / height, &self.previousPose, alpha);
+		self.lineRenderer.cullFrustum = Some(frustum);
+		self.worldText.setCullFrustum(Some(frustum));
+		// Screen space, origin bottom-left
+		self.camera2D.pos = vec2(width, height) * 0.5;
+		self.spriteBatch.cullFrustum = Some(self.camera2D.getFrustum(width, height));
+		
+		// Hover picking
+		if let Some(cursor) = self.hoverCursor && let Some((name, p)) = self.pick(cursor) {
+			let color = vec3(1.0, 1.0, 0.0);
+			self.lineRenderer.pushLine(p - Vec3::X * 0.1, color, p + Vec3::X * 0.1, color);
+			self.lineRenderer.pushLine(p - Vec3::Y * 0.1, color, p + Vec3::Y * 0.1, color);
+			self.lineRenderer.pushLine(p - Vec3::Z * 0.1, color, p + Vec3::Z * 0.1, color);
+			debugText!(self.debugOverlay, 10.0, 160.0, "Hover: {} {:.2} {:.2} {:.2}", name, p.x, p.y, p.z);
+		}
+		if let Some(name) = &self.gamepadName {
+			debugText!(self.debugOverlay, 10.0, 180.0, "Gamepad: {}", name);
 		}
+		self.pushScene(time, frameTime);
+		
+		// Reverse-Z stores near at 1, so clear to 0 and keep the closer (greater) depth
+		let reverseZ = self.camera.projection.isReverseZ(self.camera.clipDepth);
+		self.context.clearColor(0.0, 0.0, 0.0, 1.0);
+		self.context.clearDepth(if reverseZ { 0.0 } else { 1.0 });
+		self.context.depthFunc(if reverseZ { GREATER } else { LESS });
+		self.context.clear(COLOR_BUFFER_BIT | DEPTH_BUFFER_BIT);
 
-		let aspect = self.window.inner_size().width as f32 / self.window.inner_size().height as f32;
-		let projection = Mat4::perspective_rh(self.camera.fov.to_radians(), aspect, 0.1, 100.0);
-		let view = self.camera.getViewMatrix();
-		let pvm = projection * view;
+		let pvm = self.camera.getInterpolatedViewProjectionMatrix(width / height, &self.previousPose, alpha);
+		self.gpuProfiler.begin("Lines");
 		self.lineRenderer.drawFlush(&pvm);
+		self.gpuProfiler.begin("World text");
+		self.worldText.drawFlush(&pvm);
+		
+		self.context.disable(DEPTH_TEST);
+		let screenPvm = self.camera2D.getViewProjectionMatrix(width, height);
+		self.gpuProfiler.begin("Sprites");
+		self.spriteBatch.drawFlush(&screenPvm);
+		self.gpuProfiler.begin("Screen text");
+		self.screenText.drawFlush(&screenPvm);
+		
+		debugText!(self.debugOverlay, 10.0, 80.0, "Frame: {:.2} ms, min {:.2} max {:.2} p99 {:.2}, update {:.2} render {:.2}",
+			stats.avgFrameTime() * 1000.0, stats.minFrameTime() * 1000.0, stats.maxFrameTime() * 1000.0, stats.percentile(0.99) * 1000.0,
+			stats.avgUpdateTime() * 1000.0, stats.avgRenderTime() * 1000.0);
+		debugText!(self.debugOverlay, 10.0, 100.0, "FPS: {:.0} Culled lines: {}", stats.fps(), self.lineRenderer.lastCulled);
+		debugText!(self.debugOverlay, 10.0, 120.0, "Pos: {:.2} {:.2} {:.2}", self.camera.pos.x, self.camera.pos.y, self.camera.pos.z);
+		debugText!(self.debugOverlay, 10.0, 140.0, "Yaw: {:.1} Pitch: {:.1} Roll: {:.1}", self.camera.yaw, self.camera.pitch, self.camera.getRoll());
+		self.gpuProfiler.begin("Debug overlay");
+		self.debugOverlay.drawFlush(width, height);
+		self.gpuProfiler.begin("GUI");
+		self.gui.draw((self.window.inner_size().width, self.window.inner_size().height));
+		self.gpuProfiler.endFrame();
+		self.context.enable(DEPTH_TEST);
 	}
 	
 	pub fn destroy(&mut self) {
 		self.lineRenderer.destroy();
+		self.spriteBatch.destroy();
+		self.checkerTexture.destroy();
+		self.whiteTexture.destroy();
+		self.atlasTexture.destroy();
+		self.screenText.destroy();
+		self.worldText.destroy();
+		self.font.destroy();
+		self.debugOverlay.destroy();
+		self.gpuProfiler.destroy();
+		self.gui.destroy();
 	}
 }
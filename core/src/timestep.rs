@@ -0,0 +1,157 @@
+#![allow(non_snake_case)]
+
+use log::warn;
+use crate::input::InputFrame;
+
+/*
+ * Runs the simulation at a fixed rate independent of the frame rate. Frame time is accumulated
+ * and spent in whole ticks, the leftover fraction is the interpolation alpha for rendering.
+ * Input is handed to the first tick of a frame, frames without a tick carry their input over.
+ */
+pub struct FixedTimestep {
+	// Ticks per second
+	pub tickRate: f64,
+	// Ticks per frame before the remaining time is dropped, keeps a slow tick from snowballing
+	pub maxSteps: u32,
+	// Longer frames (hitches, breakpoints, background tabs) are clamped to this many seconds
+	pub maxFrameTime: f64,
+
+	accumulator: f64,
+	pending: Option<InputFrame>,
+	frameTime: f64,
+	steps: u32,
+}
+
+impl Default for FixedTimestep {
+	fn default() -> FixedTimestep {
+		FixedTimestep {
+			tickRate: 60.0,
+			maxSteps: 5,
+			maxFrameTime: 0.25,
+			accumulator: 0.0,
+			pending: None,
+			frameTime: 0.0,
+			steps: 0,
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl FixedTimestep {
+	pub fn new(tickRate: f64) -> Self {
+		FixedTimestep {
+			tickRate,
+			..FixedTimestep::default()
+		}
+	}
+
+	// Seconds per tick
+	pub fn dt(&self) -> f64 {
+		1.0 / self.tickRate.max(1.0)
+	}
+
+	// Advances by `frame.dt` and calls `tick` zero or more times with the tick length as dt
+	pub fn run(&mut self, frame: InputFrame, mut tick: impl FnMut(&InputFrame)) -> u32 {
+		let dt = self.dt();
+		self.frameTime = frame.dt;
+		self.accumulator += frame.dt.clamp(0.0, self.maxFrameTime);
+		let mut input = match self.pending.take() {
+			Some(mut pending) => {
+				pending.merge(frame);
+				pending
+			},
+			None => frame,
+		};
+
+		self.steps = 0;
+		while self.accumulator >= dt {
+			if self.steps >= self.maxSteps {
+				warn!("Dropping {:.1} ms of simulation time", self.accumulator * 1000.0);
+				self.accumulator %= dt;
+				break;
+			}
+			input.dt = dt;
+			tick(&input);
+			input = input.carryOver();
+			self.accumulator -= dt;
+			self.steps += 1;
+		}
+		self.pending = Some(input);
+		self.steps
+	}
+
+	// 0..1, how far between the last two ticks the current frame is
+	pub fn alpha(&self) -> f32 {
+		(self.accumulator / self.dt()).clamp(0.0, 1.0) as f32
+	}
+
+	// Real seconds of the last frame, unclamped
+	pub fn frameTime(&self) -> f64 {
+		self.frameTime
+	}
+
+	// Ticks run in the last frame
+	pub fn steps(&self) -> u32 {
+		self.steps
+	}
+
+	pub fn reset(&mut self) {
+		self.accumulator = 0.0;
+		self.pending = None;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use winit::keyboard::KeyCode;
+	use crate::input::InputFrame;
+	use super::FixedTimestep;
+
+	fn frame(dt: f64) -> InputFrame {
+		InputFrame {
+			dt,
+			..InputFrame::default()
+		}
+	}
+
+	#[test]
+	fn ticksAtFixedRate() {
+		// Powers of two so the accumulator is exact
+		let mut timestep = FixedTimestep::new(64.0);
+		let mut ticks = Vec::new();
+		for _ in 0..9 {
+			timestep.run(frame(2.5 / 64.0), |input| ticks.push(input.dt));
+		}
+		// 22.5 ticks worth of frames
+		assert_eq!(ticks.len(), 22);
+		assert!(ticks.iter().all(|dt| *dt == 1.0 / 64.0));
+		assert!((timestep.alpha() - 0.5).abs() < 1e-4);
+	}
+
+	#[test]
+	fn pressesSurviveFramesWithoutTicks() {
+		let mut timestep = FixedTimestep::new(10.0);
+		let mut pressed = 0;
+		timestep.run(InputFrame {
+			keysPressed: vec![KeyCode::KeyW],
+			keysHeld: vec![KeyCode::KeyW],
+			..frame(0.05)
+		}, |_| panic!("no tick expected"));
+		let steps = timestep.run(frame(0.1), |input| {
+			if input.keysPressed.contains(&KeyCode::KeyW) {
+				pressed += 1;
+			}
+		});
+		// Only the first tick of the frame sees the press
+		assert_eq!(steps, 1);
+		assert_eq!(pressed, 1);
+	}
+
+	#[test]
+	fn dropsTimeAfterMaxSteps() {
+		let mut timestep = FixedTimestep::new(60.0);
+		let steps = timestep.run(frame(10.0), |_| {});
+		assert_eq!(steps, timestep.maxSteps);
+		assert!(timestep.alpha() < 1.0);
+	}
+}
@@ -0,0 +1,223 @@
+#![allow(non_snake_case)]
+
+use std::collections::VecDeque;
+use web_time::{Duration, Instant};
+
+// Seconds spent in one frame
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct FrameSample {
+	// Start to start of the next frame
+	pub frame: f64,
+	pub update: f64,
+	pub render: f64,
+}
+
+/*
+ * Frame time statistics over the last `windowSize` frames. The runner calls `beginFrame` once per
+ * frame and brackets the update and render work, a frame's sample is complete when the next begins.
+ */
+pub struct FrameStats {
+	pub windowSize: usize,
+	samples: VecDeque<FrameSample>,
+	current: FrameSample,
+	frameStart: Option<Instant>,
+	mark: Option<Instant>,
+}
+
+impl Default for FrameStats {
+	fn default() -> FrameStats {
+		FrameStats::new(240)
+	}
+}
+
+#[allow(dead_code)]
+impl FrameStats {
+	pub fn new(windowSize: usize) -> Self {
+		FrameStats {
+			windowSize: windowSize.max(1),
+			samples: VecDeque::with_capacity(windowSize),
+			current: FrameSample::default(),
+			frameStart: None,
+			mark: None,
+		}
+	}
+
+	// Returns the seconds since the previous frame began, 0 for the first frame
+	pub fn beginFrame(&mut self) -> f64 {
+		let now = Instant::now();
+		let mut frameTime = 0.0;
+		if let Some(start) = self.frameStart {
+			frameTime = (now - start).as_secs_f64();
+			self.current.frame = frameTime;
+			self.push(self.current);
+		}
+		self.current = FrameSample::default();
+		self.frameStart = Some(now);
+		frameTime
+	}
+
+	pub fn beginUpdate(&mut self) {
+		self.mark = Some(Instant::now());
+	}
+
+	pub fn endUpdate(&mut self) {
+		if let Some(mark) = self.mark.take() {
+			self.current.update += mark.elapsed().as_secs_f64();
+		}
+	}
+
+	pub fn beginRender(&mut self) {
+		self.mark = Some(Instant::now());
+	}
+
+	pub fn endRender(&mut self) {
+		if let Some(mark) = self.mark.take() {
+			self.current.render += mark.elapsed().as_secs_f64();
+		}
+	}
+
+	pub fn push(&mut self, sample: FrameSample) {
+		while self.samples.len() >= self.windowSize {
+			self.samples.pop_front();
+		}
+		self.samples.push_back(sample);
+	}
+
+	pub fn frameStart(&self) -> Option<Instant> {
+		self.frameStart
+	}
+
+	pub fn samples(&self) -> impl Iterator<Item = &FrameSample> {
+		self.samples.iter()
+	}
+
+	pub fn latest(&self) -> Option<FrameSample> {
+		self.samples.back().copied()
+	}
+
+	pub fn fps(&self) -> f64 {
+		let avg = self.avgFrameTime();
+		if avg > 0.0 { 1.0 / avg } else { 0.0 }
+	}
+
+	pub fn minFrameTime(&self) -> f64 {
+		self.samples.iter().map(|s| s.frame).reduce(f64::min).unwrap_or(0.0)
+	}
+
+	pub fn maxFrameTime(&self) -> f64 {
+		self.samples.iter().map(|s| s.frame).reduce(f64::max).unwrap_or(0.0)
+	}
+
+	pub fn avgFrameTime(&self) -> f64 {
+		self.average(|s| s.frame)
+	}
+
+	pub fn avgUpdateTime(&self) -> f64 {
+		self.average(|s| s.update)
+	}
+
+	pub fn avgRenderTime(&self) -> f64 {
+		self.average(|s| s.render)
+	}
+
+	// Nearest rank, `percentile(0.99)` is the frame time 99% of frames are at or under
+	pub fn percentile(&self, percentile: f64) -> f64 {
+		if self.samples.is_empty() {
+			return 0.0;
+		}
+		let mut times: Vec<f64> = self.samples.iter().map(|s| s.frame).collect();
+		times.sort_by(f64::total_cmp);
+		let rank = (percentile.clamp(0.0, 1.0) * times.len() as f64).ceil() as usize;
+		times[rank.saturating_sub(1)]
+	}
+
+	pub fn reset(&mut self) {
+		self.samples.clear();
+		self.current = FrameSample::default();
+		self.frameStart = None;
+		self.mark = None;
+	}
+
+	fn average(&self, value: impl Fn(&FrameSample) -> f64) -> f64 {
+		if self.samples.is_empty() {
+			return 0.0;
+		}
+		self.samples.iter().map(value).sum::<f64>() / self.samples.len() as f64
+	}
+}
+
+/*
+ * Frame pacing settings, changed at runtime and applied by the runner. The cap delays the next frame
+ * through `ControlFlow::WaitUntil`, vsync maps to the swap interval on native. Browsers always sync
+ * to the display so vsync is ignored on web.
+ */
+#[derive(Clone, Debug)]
+pub struct FrameLimiter {
+	// Frames per second, None for uncapped
+	pub maxFps: Option<f64>,
+	pub vsync: bool,
+}
+
+impl Default for FrameLimiter {
+	fn default() -> FrameLimiter {
+		FrameLimiter {
+			maxFps: None,
+			vsync: true,
+		}
+	}
+}
+
+#[allow(dead_code)]
+impl FrameLimiter {
+	// When the next frame is due, None if it's due already or there's no cap
+	pub fn waitUntil(&self, stats: &FrameStats) -> Option<Instant> {
+		let maxFps = self.maxFps.filter(|fps| *fps > 0.0)?;
+		let next = stats.frameStart()? + Duration::from_secs_f64(1.0 / maxFps);
+		(Instant::now() < next).then_some(next)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{FrameSample, FrameStats};
+
+	fn frame(frame: f64) -> FrameSample {
+		FrameSample {
+			frame,
+			..FrameSample::default()
+		}
+	}
+
+	#[test]
+	fn rollingWindowStats() {
+		let mut stats = FrameStats::new(100);
+		// Oldest samples fall out of the window
+		for _ in 0..50 {
+			stats.push(frame(1.0));
+		}
+		for i in 1..=100 {
+			stats.push(FrameSample {
+				frame: i as f64 / 1000.0,
+				update: 0.002,
+				render: 0.004,
+			});
+		}
+		assert_eq!(stats.samples().count(), 100);
+		assert_eq!(stats.minFrameTime(), 0.001);
+		assert_eq!(stats.maxFrameTime(), 0.1);
+		assert!((stats.avgFrameTime() - 0.0505).abs() < 1e-9);
+		assert_eq!(stats.percentile(0.5), 0.05);
+		assert_eq!(stats.percentile(0.99), 0.099);
+		assert_eq!(stats.percentile(1.0), 0.1);
+		assert!((stats.avgUpdateTime() - 0.002).abs() < 1e-9);
+		assert!((stats.avgRenderTime() - 0.004).abs() < 1e-9);
+	}
+
+	#[test]
+	fn emptyStatsAreZero() {
+		let stats = FrameStats::default();
+		assert_eq!(stats.fps(), 0.0);
+		assert_eq!(stats.percentile(0.99), 0.0);
+		assert_eq!(stats.latest(), None);
+	}
+}
@@ -0,0 +1,58 @@
+#![allow(non_snake_case)]
+
+use std::sync::OnceLock;
+use serde::Serialize;
+use web_time::Instant;
+
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+
+// Microseconds since the first call, the shared clock for every trace event
+pub fn traceTime() -> f64 {
+	EPOCH.get_or_init(Instant::now).elapsed().as_secs_f64() * 1_000_000.0
+}
+
+/*
+ * A complete ("X") event in the Chrome trace event format, loads in chrome://tracing, Perfetto and
+ * Speedscope. Times are in microseconds.
+ */
+#[derive(Clone, Debug, Serialize)]
+pub struct TraceEvent {
+	pub name: String,
+	#[serde(rename = "cat")]
+	pub category: &'static str,
+	#[serde(rename = "ph")]
+	pub phase: &'static str,
+	pub ts: f64,
+	pub dur: f64,
+	pub pid: u32,
+	// Separate rows for CPU and GPU
+	pub tid: u32,
+}
+
+impl TraceEvent {
+	pub fn complete(name: &str, category: &'static str, tid: u32, ts: f64, dur: f64) -> TraceEvent {
+		TraceEvent {
+			name: name.to_string(),
+			category,
+			phase: "X",
+			ts,
+			dur,
+			pid: 1,
+			tid,
+		}
+	}
+}
+
+#[derive(Serialize)]
+struct ChromeTrace<'a> {
+	traceEvents: Vec<&'a TraceEvent>,
+	displayTimeUnit: &'static str,
+}
+
+pub fn chromeTraceJson<'a>(events: impl IntoIterator<Item = &'a TraceEvent>) -> Result<String, String> {
+	let trace = ChromeTrace {
+		traceEvents: events.into_iter().collect(),
+		displayTimeUnit: "ms",
+	};
+	serde_json::to_string(&trace).map_err(|e| format!("Failed to write trace: {}", e))
+}
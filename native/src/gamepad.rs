@@ -0,0 +1,101 @@
+#![allow(non_snake_case)]
+
+use core::input::{GamepadAxis, GamepadButton, Gamepads};
+use gilrs::{Axis, Button, EventType, Gilrs};
+use log::warn;
+
+/*
+ * Feeds gilrs events into the core gamepad state
+ */
+pub struct GilrsBackend {
+	gilrs: Option<Gilrs>,
+}
+
+fn mapButton(button: Button) -> Option<GamepadButton> {
+	Some(match button {
+		Button::South => GamepadButton::South,
+		Button::East => GamepadButton::East,
+		Button::West => GamepadButton::West,
+		Button::North => GamepadButton::North,
+		Button::LeftTrigger => GamepadButton::LeftBumper,
+		Button::RightTrigger => GamepadButton::RightBumper,
+		Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+		Button::RightTrigger2 => GamepadButton::RightTrigger,
+		Button::Select => GamepadButton::Select,
+		Button::Start => GamepadButton::Start,
+		Button::Mode => GamepadButton::Mode,
+		Button::LeftThumb => GamepadButton::LeftStick,
+		Button::RightThumb => GamepadButton::RightStick,
+		Button::DPadUp => GamepadButton::DPadUp,
+		Button::DPadDown => GamepadButton::DPadDown,
+		Button::DPadLeft => GamepadButton::DPadLeft,
+		Button::DPadRight => GamepadButton::DPadRight,
+		_ => return None,
+	})
+}
+
+fn mapAxis(axis: Axis) -> Option<GamepadAxis> {
+	Some(match axis {
+		Axis::LeftStickX => GamepadAxis::LeftStickX,
+		Axis::LeftStickY => GamepadAxis::LeftStickY,
+		Axis::RightStickX => GamepadAxis::RightStickX,
+		Axis::RightStickY => GamepadAxis::RightStickY,
+		Axis::LeftZ => GamepadAxis::LeftTrigger,
+		Axis::RightZ => GamepadAxis::RightTrigger,
+		_ => return None,
+	})
+}
+
+impl GilrsBackend {
+	// Gamepads are optional, the app keeps running without them
+	pub fn new(gamepads: &mut Gamepads) -> Self {
+		let gilrs = match Gilrs::new() {
+			Ok(gilrs) => Some(gilrs),
+			Err(e) => {
+				warn!("Gamepads unavailable: {}", e);
+				None
+			}
+		};
+		if let Some(gilrs) = &gilrs {
+			for (id, gamepad) in gilrs.gamepads() {
+				gamepads.connect(id.into(), gamepad.name());
+			}
+		}
+		GilrsBackend { gilrs }
+	}
+
+	pub fn poll(&mut self, gamepads: &mut Gamepads) {
+		let Some(gilrs) = &mut self.gilrs else {
+			return;
+		};
+		while let Some(event) = gilrs.next_event() {
+			let id: usize = event.id.into();
+			match event.event {
+				EventType::Connected => {
+					let name = gilrs.gamepad(event.id).name().to_string();
+					gamepads.connect(id, &name);
+				}
+				EventType::Disconnected => gamepads.disconnect(id),
+				EventType::ButtonPressed(button, _) => {
+					if let Some(button) = mapButton(button) {
+						gamepads.setButton(id, button, true);
+					}
+				}
+				EventType::ButtonReleased(button, _) => {
+					if let Some(button) = mapButton(button) {
+						gamepads.setButton(id, button, false);
+					}
+				}
+				// Analog triggers report through button values
+				EventType::ButtonChanged(Button::LeftTrigger2, value, _) => gamepads.setAxis(id, GamepadAxis::LeftTrigger, value),
+				EventType::ButtonChanged(Button::RightTrigger2, value, _) => gamepads.setAxis(id, GamepadAxis::RightTrigger, value),
+				EventType::AxisChanged(axis, value, _) => {
+					if let Some(axis) = mapAxis(axis) {
+						gamepads.setAxis(id, axis, value);
+					}
+				}
+				_ => {}
+			}
+		}
+	}
+}
@@ -1,5 +1,11 @@
 #![allow(non_snake_case)]
 
+mod gamepad;
+
+use core::input::{Gamepads, InputSession, REPLAY_DT};
+use core::render::{enableClipControl, enableGlDebug, GlDebugSeverity};
+use core::timestep::FixedTimestep;
+use core::timing::FrameStats;
 use core::TestApp;
 use glutin::config::ConfigTemplateBuilder;
 use glutin::context::{ContextApi, ContextAttributesBuilder, PossiblyCurrentContext};
@@ -9,14 +15,15 @@ use glutin::surface::{Surface, SwapInterval, WindowSurface};
 use glutin_winit::{DisplayBuilder, GlWindow};
 use raw_window_handle::{HasWindowHandle, RawWindowHandle};
 use std::num::NonZeroU32;
+use std::panic::{self, AssertUnwindSafe};
 use std::rc::Rc;
 use log::{debug, error, info, trace, warn};
 use winit::application::ApplicationHandler;
 use winit::dpi::PhysicalSize;
-use winit::event::{DeviceEvent, DeviceId, StartCause, WindowEvent};
+use winit::event::{DeviceEvent, DeviceId, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
 use winit::window::{Window, WindowAttributes, WindowId};
-use winit_input_helper::WinitInputHelper;
+use crate::gamepad::GilrsBackend;
 
 const WIDTH: u32 = 800;
 const HEIGHT: u32 = 600;
@@ -25,20 +32,24 @@ struct State {
     glSurface: Surface<WindowSurface>,
     glContext: PossiblyCurrentContext,
     testApp: TestApp,
+	// Swap interval currently applied
+	vsync: bool,
 }
 
 #[derive(Default)]
 struct App {
 	window: Option<Rc<Window>>,
     state: Option<State>,
-	input: WinitInputHelper,
+	gamepads: Gamepads,
+	gamepadBackend: Option<GilrsBackend>,
+	session: InputSession,
+	// --gl-debug, debug context with KHR_debug output
+	glDebug: bool,
+	timestep: FixedTimestep,
+	stats: FrameStats,
 }
 
 impl ApplicationHandler for App {
-	fn new_events(&mut self, _eventLoop: &ActiveEventLoop, _cause: StartCause) {
-		self.input.step();
-	}
-	
 	fn resumed(&mut self, eventLoop: &ActiveEventLoop) {
 		if self.state.is_some() {
 			return;
@@ -75,9 +86,10 @@ impl ApplicationHandler for App {
 				major: 4,
 				minor: 1,
 			})))
+			.with_debug(self.glDebug)
 			.build(rwh);
 
-		let (window, gl, glSurface, glContext) = unsafe {
+		let (window, gl, glSurface, glContext, clipDepth) = unsafe {
 			let notCurrentGlContext = glDisplay
 				.create_context(&glConfig, &contextAttributes)
 				.unwrap();
@@ -89,22 +101,29 @@ impl ApplicationHandler for App {
 				.unwrap();
 
 			let glContext = notCurrentGlContext.make_current(&glSurface).unwrap();
-			let gl = Rc::new(glow::Context::from_loader_function_cstr(|s| glDisplay.get_proc_address(s)));
+			let mut gl = glow::Context::from_loader_function_cstr(|s| glDisplay.get_proc_address(s));
+			if self.glDebug {
+				enableGlDebug(&mut gl, GlDebugSeverity::Low);
+			}
+			let clipDepth = enableClipControl(&gl, |s| glDisplay.get_proc_address(s));
+			let gl = Rc::new(gl);
 			glSurface.set_swap_interval(&glContext, SwapInterval::Wait(NonZeroU32::new(1).unwrap())).unwrap();
 			// glSurface.set_swap_interval(&glContext, SwapInterval::DontWait).unwrap();
 			
 			// gl.viewport(0, 0, 800, 600);
 
-			(window, gl, glSurface, glContext)
+			(window, gl, glSurface, glContext, clipDepth)
 		};
 
-		let testApp = TestApp::new(window.clone(), gl.clone(), (WIDTH, HEIGHT));
+		let testApp = TestApp::new(window.clone(), gl.clone(), (WIDTH, HEIGHT), clipDepth);
+		self.gamepadBackend = Some(GilrsBackend::new(&mut self.gamepads));
 
 		self.window = Some(window.clone());
 		self.state = Some(State {
 			glSurface,
 			glContext,
 			testApp,
+			vsync: true,
 		});
 
 		// self.window = Some(eventLoop.create_window(Window::default_attributes()
@@ -114,7 +133,12 @@ impl ApplicationHandler for App {
 	}
 	
 	fn window_event(&mut self, eventLoop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
-		self.input.process_window_event(&event);
+		// Replays only see recorded input, live clicks on the GUI or cursor capture would change the outcome
+		let consumed = match self.state {
+			Some(ref mut state) if !self.session.isReplaying() => state.testApp.windowEvent(&event),
+			_ => false,
+		};
+		self.session.processWindowEvent(&event, consumed);
 		match event {
 			// WindowEvent::ActivationTokenDone { .. } => {},
 			WindowEvent::Resized(size) => {
@@ -150,36 +174,95 @@ impl ApplicationHandler for App {
 			// WindowEvent::ScaleFactorChanged { .. } => {},
 			// WindowEvent::ThemeChanged(_) => {},
 			// WindowEvent::Occluded(_) => {},
+			// One frame per redraw, so frame stats and the limiter count presented frames
 			WindowEvent::RedrawRequested => {
 				if let Some(ref mut state) = self.state {
-					state.testApp.render();
+					core::profileScope!("RedrawRequested");
+					self.gamepads.step();
+					if let Some(ref mut backend) = self.gamepadBackend {
+						backend.poll(&mut self.gamepads);
+					}
+					let frameTime = self.stats.beginFrame();
+					let Some(frame) = self.session.nextFrame(frameTime, &self.gamepads) else {
+						info!("Replay finished");
+						eventLoop.exit();
+						return;
+					};
+					self.stats.beginUpdate();
+					self.timestep.run(frame, |input| state.testApp.update(input, eventLoop));
+					self.stats.endUpdate();
+					if let Err(e) = self.session.autosave() {
+						error!("{}", e);
+					}
+					
+					self.stats.beginRender();
+					state.testApp.render(&self.timestep, &self.stats);
+					self.stats.endRender();
+					core::profiler::begin("swap_buffers");
 					state.glSurface.swap_buffers(&state.glContext).unwrap();
+					core::profiler::end();
 				}
-
-				self.window.as_ref().unwrap().request_redraw();
 			},
 			_ => (),
 		}
 	}
 	
 	fn device_event(&mut self, _eventLoop: &ActiveEventLoop, _id: DeviceId, event: DeviceEvent) {
-		self.input.process_device_event(&event);
+		self.session.processDeviceEvent(&event);
 	}
 
 	fn about_to_wait(&mut self, eventLoop: &ActiveEventLoop) {
-		self.input.end_step();
-        if let Some(ref mut state) = self.state {
-            state.testApp.update(self.input.delta_time().unwrap().as_secs_f64(), &self.input, eventLoop);
-        }
-    }
+		let Some(ref mut state) = self.state else {
+			return;
+		};
+		// Frame cap, sleep until the next frame is due
+		if let Some(deadline) = state.testApp.frameLimiter().waitUntil(&self.stats) {
+			eventLoop.set_control_flow(ControlFlow::WaitUntil(deadline));
+			return;
+		}
+		eventLoop.set_control_flow(ControlFlow::Poll);
+		core::profileScope!("about_to_wait");
+		
+		let vsync = state.testApp.frameLimiter().vsync;
+		if vsync != state.vsync {
+			let interval = if vsync { SwapInterval::Wait(NonZeroU32::new(1).unwrap()) } else { SwapInterval::DontWait };
+			match state.glSurface.set_swap_interval(&state.glContext, interval) {
+				Ok(()) => info!("VSync: {}", vsync),
+				Err(e) => warn!("Failed to set the swap interval: {}", e),
+			}
+			state.vsync = vsync;
+		}
+		self.window.as_ref().unwrap().request_redraw();
+	}
 
 	fn exiting(&mut self, _eventLoop: &ActiveEventLoop) {
+		if let Err(e) = self.session.finish() {
+			error!("{}", e);
+		}
 		if let Some(ref mut state) = self.state {
 			state.testApp.destroy();
 		}
 	}
 }
 
+// --record <file> saves the session's input, --replay <file> plays it back and exits.
+// Replays step a fixed 1/60 s per frame, --replay-dt <seconds> changes it
+fn inputSession() -> InputSession {
+	let args: Vec<String> = std::env::args().collect();
+	let value = |flag: &str| args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1));
+	if let Some(key) = value("--replay") {
+		let fixedDt = value("--replay-dt").and_then(|dt| dt.parse().ok()).unwrap_or(REPLAY_DT);
+		match InputSession::replay(key, Some(fixedDt)) {
+			Ok(session) => return session,
+			Err(e) => error!("{}", e),
+		}
+	} else if let Some(key) = value("--record") {
+		info!("Recording input to {}", key);
+		return InputSession::record(key);
+	}
+	InputSession::default()
+}
+
 fn main() {
 	// let logFile = File::create("native.log").expect("Failed to create log file");
 	
@@ -193,7 +276,10 @@ fn main() {
 		.module_with_line(true)
 		.module_filter(|module| module.contains(""))
 		.compress(true)
-		.format(|record, _tee| format!("[{}] [{}] {}\n", chrono::Local::now(), record.level(), record.args()))
+		.format(|record, _tee| {
+			core::render::recordDebugLog(record);
+			format!("[{}] [{}] {}\n", chrono::Local::now(), record.level(), record.args())
+		})
 		.level("info")
 		.start();
 	log_panics::init();
@@ -208,10 +294,24 @@ fn main() {
 	// panic!("panic");
 
     let eventLoop = EventLoop::new().unwrap();
-    eventLoop
-        .run_app(&mut App {
-			window: None,
-			state: None,
-			input: WinitInputHelper::new(),
-		}).expect("Failed to run event loop");
+	let mut app = App {
+		window: None,
+		state: None,
+		gamepads: Gamepads::default(),
+		gamepadBackend: None,
+		session: inputSession(),
+		glDebug: std::env::args().any(|a| a == "--gl-debug"),
+		timestep: FixedTimestep::default(),
+		stats: FrameStats::default(),
+	};
+	match panic::catch_unwind(AssertUnwindSafe(|| eventLoop.run_app(&mut app))) {
+		Ok(result) => result.expect("Failed to run event loop"),
+		// `exiting` is skipped when a handler panics, keep the input that led up to it
+		Err(payload) => {
+			if let Err(e) = app.session.saveRecording() {
+				error!("{}", e);
+			}
+			panic::resume_unwind(payload);
+		},
+	}
 }
@@ -0,0 +1,13 @@
+#version 330 core
+
+uniform sampler2D u_texture;
+
+in vec2 f_uv;
+in vec4 f_color;
+
+out vec4 o_color;
+
+void main() {
+	// Premultiplied alpha
+	o_color = f_color * texture(u_texture, f_uv);
+}
\ No newline at end of file
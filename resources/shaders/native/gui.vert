@@ -0,0 +1,17 @@
+#version 330 core
+
+uniform vec2 u_screenSize;
+
+in vec2 i_position;
+in vec2 i_uv;
+in vec4 i_color;
+
+out vec2 f_uv;
+out vec4 f_color;
+
+void main() {
+	// Points, origin top-left
+	gl_Position = vec4(2. * i_position.x / u_screenSize.x - 1., 1. - 2. * i_position.y / u_screenSize.y, 0., 1.);
+	f_uv = i_uv;
+	f_color = i_color;
+}
\ No newline at end of file
@@ -0,0 +1,16 @@
+#version 330 core
+
+uniform sampler2D u_texture;
+
+in vec2 f_uv;
+in vec4 f_color;
+
+out vec4 o_color;
+
+void main() {
+	o_color = texture(u_texture, f_uv) * f_color;
+	// Keeps glyph/cutout edges from writing depth
+	if (o_color.a < 0.01) {
+		discard;
+	}
+}
\ No newline at end of file
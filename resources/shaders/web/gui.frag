@@ -0,0 +1,14 @@
+#version 300 es
+precision mediump float;
+
+uniform sampler2D u_texture;
+
+in vec2 f_uv;
+in vec4 f_color;
+
+out vec4 o_color;
+
+void main() {
+	// Premultiplied alpha
+	o_color = f_color * texture(u_texture, f_uv);
+}
\ No newline at end of file
@@ -0,0 +1,17 @@
+#version 300 es
+precision mediump float;
+
+uniform mat4 u_pvm;
+
+in vec3 i_position;
+in vec2 i_uv;
+in vec4 i_color;
+
+out vec2 f_uv;
+out vec4 f_color;
+
+void main() {
+	gl_Position = u_pvm * vec4(i_position, 1.);
+	f_uv = i_uv;
+	f_color = i_color;
+}
\ No newline at end of file
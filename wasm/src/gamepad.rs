@@ -0,0 +1,83 @@
+#![allow(non_snake_case)]
+
+use core::input::{GamepadAxis, GamepadButton, Gamepads};
+use wasm_bindgen::JsCast;
+
+/*
+ * Polls the browser Gamepad API into the core gamepad state. Browsers only expose pads after a
+ * button press, hot-plugging is detected by comparing the connected set between polls.
+ */
+#[derive(Default)]
+pub struct WebGamepads {
+	connected: Vec<usize>,
+}
+
+// "standard" mapping button indices
+const BUTTONS: [(u32, GamepadButton); 17] = [
+	(0, GamepadButton::South),
+	(1, GamepadButton::East),
+	(2, GamepadButton::West),
+	(3, GamepadButton::North),
+	(4, GamepadButton::LeftBumper),
+	(5, GamepadButton::RightBumper),
+	(6, GamepadButton::LeftTrigger),
+	(7, GamepadButton::RightTrigger),
+	(8, GamepadButton::Select),
+	(9, GamepadButton::Start),
+	(10, GamepadButton::LeftStick),
+	(11, GamepadButton::RightStick),
+	(12, GamepadButton::DPadUp),
+	(13, GamepadButton::DPadDown),
+	(14, GamepadButton::DPadLeft),
+	(15, GamepadButton::DPadRight),
+	(16, GamepadButton::Mode),
+];
+
+impl WebGamepads {
+	pub fn poll(&mut self, gamepads: &mut Gamepads) {
+		let Some(navigator) = web_sys::window().map(|w| w.navigator()) else {
+			return;
+		};
+		let Ok(pads) = navigator.get_gamepads() else {
+			return;
+		};
+
+		let mut seen = Vec::new();
+		// Disconnected slots are null
+		for pad in pads.iter().filter_map(|pad| pad.dyn_into::<web_sys::Gamepad>().ok()) {
+			if !pad.connected() {
+				continue;
+			}
+			let id = pad.index() as usize;
+			seen.push(id);
+			gamepads.connect(id, &pad.id());
+
+			let buttons = pad.buttons();
+			for (index, button) in BUTTONS {
+				if let Ok(state) = buttons.get(index).dyn_into::<web_sys::GamepadButton>() {
+					gamepads.setButton(id, button, state.pressed());
+					match button {
+						GamepadButton::LeftTrigger => gamepads.setAxis(id, GamepadAxis::LeftTrigger, state.value() as f32),
+						GamepadButton::RightTrigger => gamepads.setAxis(id, GamepadAxis::RightTrigger, state.value() as f32),
+						_ => {}
+					}
+				}
+			}
+
+			// Web sticks are +Y down
+			let axes = pad.axes();
+			let axis = |index: u32| axes.get(index).as_f64().unwrap_or(0.0) as f32;
+			gamepads.setAxis(id, GamepadAxis::LeftStickX, axis(0));
+			gamepads.setAxis(id, GamepadAxis::LeftStickY, -axis(1));
+			gamepads.setAxis(id, GamepadAxis::RightStickX, axis(2));
+			gamepads.setAxis(id, GamepadAxis::RightStickY, -axis(3));
+		}
+
+		for id in &self.connected {
+			if !seen.contains(id) {
+				gamepads.disconnect(*id);
+			}
+		}
+		self.connected = seen;
+	}
+}
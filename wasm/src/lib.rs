@@ -4,23 +4,50 @@
 // Run (npn): http-server
 // Run (py3): python -m http.server
 
+mod gamepad;
+
+use core::camera::ClipDepth;
+use core::input::{Gamepads, InputSession, REPLAY_DT};
+use core::render::{enableGlDebug, GlDebugSeverity};
+use core::timestep::FixedTimestep;
+use core::timing::FrameStats;
 use core::TestApp;
 use std::rc::Rc;
-use log::{debug, error, info, trace, warn};
+use log::{debug, error, info, trace, warn, Level, LevelFilter, Log, Metadata, Record};
 use wasm_bindgen::prelude::*;
 use web_sys::WebGl2RenderingContext;
 use winit::application::ApplicationHandler;
 // use winit::dpi::PhysicalSize;
-use winit::event::{DeviceEvent, DeviceId, StartCause, WindowEvent};
+use winit::event::{DeviceEvent, DeviceId, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
 use winit::window::{Window, WindowAttributes, WindowId};
-use winit_input_helper::WinitInputHelper;
 
 use winit::platform::web::WindowAttributesExtWebSys;
+use crate::gamepad::WebGamepads;
 
 const WIDTH: u32 = 800;
 const HEIGHT: u32 = 600;
 
+// Console output plus the core debug overlay log
+struct WebLogger;
+
+impl Log for WebLogger {
+	fn enabled(&self, metadata: &Metadata) -> bool {
+		metadata.level() <= Level::Info
+	}
+
+	fn log(&self, record: &Record) {
+		if self.enabled(record.metadata()) {
+			console_log::log(record);
+			core::render::recordDebugLog(record);
+		}
+	}
+
+	fn flush(&self) {}
+}
+
+static LOGGER: WebLogger = WebLogger;
+
 struct State {
 	testApp: TestApp,
 }
@@ -29,14 +56,14 @@ struct State {
 struct App {
 	window: Option<Rc<Window>>,
 	state: Option<State>,
-	input: WinitInputHelper,
+	gamepads: Gamepads,
+	webGamepads: WebGamepads,
+	session: InputSession,
+	timestep: FixedTimestep,
+	stats: FrameStats,
 }
 
 impl ApplicationHandler for App {
-	fn new_events(&mut self, _eventLoop: &ActiveEventLoop, _cause: StartCause) {
-		self.input.step();
-	}
-	
 	fn resumed(&mut self, eventLoop: &ActiveEventLoop) {
 		eventLoop.set_control_flow(ControlFlow::Poll);
 		// self.window = Some(web_sys::window().unwrap());
@@ -55,7 +82,12 @@ impl ApplicationHandler for App {
 		canvas.set_height(HEIGHT);
 
 		let webGlContext = canvas.get_context("webgl2").unwrap().unwrap().dyn_into::<WebGl2RenderingContext>().unwrap();
-		let gl = Rc::new(glow::Context::from_webgl2_context(webGlContext));
+		let mut gl = glow::Context::from_webgl2_context(webGlContext);
+		// ?gldebug checks for GL errors after calls, WebGL has no debug output
+		if queryParam("gldebug").is_some() {
+			enableGlDebug(&mut gl, GlDebugSeverity::Low);
+		}
+		let gl = Rc::new(gl);
 		
 		// unsafe {
 		// 	gl.viewport(0, 0, 800, 600);
@@ -69,7 +101,8 @@ impl ApplicationHandler for App {
 		let window = eventLoop.create_window(attributes).unwrap();
 		let window = Rc::new(window);
 		
-		let testApp = TestApp::new(window.clone(), gl.clone(), (WIDTH, HEIGHT));
+		// WebGL2 has no clip control, depth stays -1..1 and reverse-Z is off
+		let testApp = TestApp::new(window.clone(), gl.clone(), (WIDTH, HEIGHT), ClipDepth::NegativeOneToOne);
 
 		self.window = Some(window.clone());
 		self.state = Some(State {
@@ -80,7 +113,12 @@ impl ApplicationHandler for App {
 	}
 
 	fn window_event(&mut self, eventLoop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
-		self.input.process_window_event(&event);
+		// Replays only see recorded input, live clicks on the GUI or cursor capture would change the outcome
+		let consumed = match self.state {
+			Some(ref mut state) if !self.session.isReplaying() => state.testApp.windowEvent(&event),
+			_ => false,
+		};
+		self.session.processWindowEvent(&event, consumed);
 		match event {
 			// WindowEvent::ActivationTokenDone { .. } => {},
 			// WindowEvent::Resized(size) => unsafe {
@@ -97,7 +135,12 @@ impl ApplicationHandler for App {
 			// WindowEvent::DroppedFile(_) => {},
 			// WindowEvent::HoveredFile(_) => {},
 			// WindowEvent::HoveredFileCancelled => {},
-			// WindowEvent::Focused(_) => {},
+			// Pages are rarely shut down cleanly, save recordings when the tab loses focus instead
+			WindowEvent::Focused(false) => {
+				if let Err(e) = self.session.saveRecording() {
+					error!("{}", e);
+				}
+			},
 			// WindowEvent::KeyboardInput { .. } => {},
 			// WindowEvent::ModifiersChanged(_) => {},
 			// WindowEvent::Ime(_) => {},
@@ -116,39 +159,95 @@ impl ApplicationHandler for App {
 			// WindowEvent::ScaleFactorChanged { .. } => {},
 			// WindowEvent::ThemeChanged(_) => {},
 			// WindowEvent::Occluded(_) => {},
+			// One frame per redraw, which the browser ties to the display, so stats count presented frames
 			WindowEvent::RedrawRequested => {
 				if let Some(ref mut state) = self.state {
-					state.testApp.render();
+					core::profileScope!("RedrawRequested");
+					self.gamepads.step();
+					self.webGamepads.poll(&mut self.gamepads);
+					let dt = self.stats.beginFrame();
+					let frame = match self.session.nextFrame(dt, &self.gamepads) {
+						Some(frame) => frame,
+						// The page keeps running, carry on with live input
+						None => {
+							info!("Replay finished");
+							let _ = self.session.finish();
+							self.session.nextFrame(dt, &self.gamepads).unwrap_or_default()
+						},
+					};
+					self.stats.beginUpdate();
+					self.timestep.run(frame, |input| state.testApp.update(input, eventLoop));
+					self.stats.endUpdate();
+					if let Err(e) = self.session.autosave() {
+						error!("{}", e);
+					}
+					
+					self.stats.beginRender();
+					state.testApp.render(&self.timestep, &self.stats);
+					self.stats.endRender();
 				}
-
-				self.window.as_ref().unwrap().request_redraw();
 			},
 			_ => (),
 		}
 	}
 	
 	fn device_event(&mut self, _eventLoop: &ActiveEventLoop, _id: DeviceId, event: DeviceEvent) {
-		self.input.process_device_event(&event);
+		self.session.processDeviceEvent(&event);
 	}
 	
 	fn about_to_wait(&mut self, eventLoop: &ActiveEventLoop) {
-		self.input.end_step();
-		if let Some(ref mut state) = self.state {
-			state.testApp.update(self.input.delta_time().unwrap().as_secs_f64(), &self.input, eventLoop);
+		let Some(ref mut state) = self.state else {
+			return;
+		};
+		// Frame cap, vsync is up to the browser
+		if let Some(deadline) = state.testApp.frameLimiter().waitUntil(&self.stats) {
+			eventLoop.set_control_flow(ControlFlow::WaitUntil(deadline));
+			return;
 		}
+		eventLoop.set_control_flow(ControlFlow::Poll);
+		self.window.as_ref().unwrap().request_redraw();
 	}
 
 	fn exiting(&mut self, _eventLoop: &ActiveEventLoop) {
+		if let Err(e) = self.session.finish() {
+			error!("{}", e);
+		}
 		if let Some(ref mut state) = self.state {
 			state.testApp.destroy();
 		}
 	}
 }
 
+// Value of `?name=value` in the page URL, empty for a bare `?name`
+fn queryParam(name: &str) -> Option<String> {
+	let search = web_sys::window().and_then(|w| w.location().search().ok()).unwrap_or_default();
+	search.trim_start_matches('?')
+		.split('&')
+		.map(|p| p.split_once('=').unwrap_or((p, "")))
+		.find(|(k, _)| *k == name)
+		.map(|(_, v)| v.to_string())
+}
+
+// ?record=<key> records input to localStorage, ?replay=<key> plays it back at a fixed 1/60 s per frame
+// (&replaydt=<seconds> changes it)
+fn inputSession() -> InputSession {
+	if let Some(key) = queryParam("replay") {
+		let fixedDt = queryParam("replaydt").and_then(|dt| dt.parse().ok()).unwrap_or(REPLAY_DT);
+		match InputSession::replay(&key, Some(fixedDt)) {
+			Ok(session) => return session,
+			Err(e) => error!("{}", e),
+		}
+	} else if let Some(key) = queryParam("record") {
+		info!("Recording input to {}", key);
+		return InputSession::record(&key);
+	}
+	InputSession::default()
+}
+
 #[wasm_bindgen(start)]
 pub fn mainJs() -> Result<(), JsValue> {
 	console_error_panic_hook::set_once();
-	console_log::init_with_level(log::Level::Info).unwrap_throw();
+	log::set_logger(&LOGGER).map(|()| log::set_max_level(LevelFilter::Info)).unwrap_throw();
 	
 	info!("Hello, world!");
 	
@@ -162,7 +261,11 @@ pub fn mainJs() -> Result<(), JsValue> {
 	eventLoop.run_app(&mut App {
 		window: None,
 		state: None,
-		input: WinitInputHelper::new(),
+		gamepads: Gamepads::default(),
+		webGamepads: WebGamepads::default(),
+		session: inputSession(),
+		timestep: FixedTimestep::default(),
+		stats: FrameStats::default(),
 	}).expect("Failed to run event loop");
 	
 	// let document = web_sys::window().unwrap().document().unwrap();